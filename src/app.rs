@@ -1,9 +1,30 @@
+use crate::audit::{self, Action, AuditEvent};
+use crate::bundle::{self, Incoming, MergeSummary};
+use crate::credential;
+use crate::discover::{self, Candidate};
+use crate::git::{GitOutput, GitRunner, SystemGit};
+use crate::inject::{self, ConfigChange, InjectScope, Restore};
+use crate::input::{self, TextInput};
+use crate::mouse::{ClickMap, LastClick};
+use crate::pair;
+use crate::provider::{self, Provider};
+use crate::registry::{self, RepoRecord};
+use crate::rotate::{self, ForgeApi, TokenValidator};
+use crate::scan::{self, ScanMessage, ScannedRepo};
+use crate::scrub::{self, Finding};
+use crate::store::{self, StoreLock};
+use crate::template::{self, Template};
+use crate::trash::{self, TrashedEntry};
 use homedir::my_home;
-use regex::Regex;
-use std::fs::File;
-use std::process::Command;
-use std::{fs, path::PathBuf};
+use serde::Serialize;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Receiver;
+use std::sync::Arc;
 
+#[derive(Clone, Copy, PartialEq)]
 pub enum CurrentScreen {
     Main,
     Editing,
@@ -12,108 +33,701 @@ pub enum CurrentScreen {
     Deleting,
     Cloning,
     Injecting,
+    // browse profiles deleted in the last days and restore them
+    Trash,
+    // the store was changed by someone else since it was loaded, ask whether to merge or reload
+    Conflict,
+    // there are unsaved changes, ask whether to save them before quitting
+    Quitting,
+    // add and remove the extra git config keys of the selected profile
+    ConfigEditor,
+    // display a prompt asking whether to remove the keys gat wrote from the repo
+    Removing,
+    // find repos below a directory and inject profiles into many of them at once
+    Scanning,
+    // an edited identity is used in known repos, ask whether to inject it there again
+    Propagating,
+    // list remote urls with embedded tokens and ask whether to clean them
+    Scrubbing,
+    // ask for a new token of the selected profile, then show what was updated
+    Rotating,
+    // pick profiles found in existing git, credential and ssh configuration
+    Importing,
+    // merge the profiles of an exported bundle, resolving alias conflicts
+    Bundle,
+    // type the path of another repo to work on, with completion
+    ChoosingRepo,
+    // pick the profiles to add as co-authors to commits in the target repo
+    Pairing,
+    // browse and filter the audit log
+    AuditLog,
+    // pick the template a new profile starts from
+    ChoosingTemplate,
+    // list every key of the main screen, the hint line only has room for a few
+    Help,
 }
 
+#[derive(Clone, Copy, PartialEq, Debug)]
 pub enum CurrentlyEditing {
     Alias,
     Username,
     Email,
     Token,
     Nickname,
+    Host,
+    Provider,
+    ConfigKey,
+    ConfigValue,
 }
 
+#[derive(Clone, Default)]
 pub struct Entry {
     pub alias: String,    // an alias displayed to the user when browsing stored keys
     pub username: String, // the git username to be used for commits
     pub email: String,    // email used in commits
     pub pa_token: String, // personal access token used for login credentials in git
     pub nickname: String,
+    pub host: String, // forge host the profile belongs to, e.g. github.com, used for suggestions
+    pub provider: Option<Provider>, // how the token is presented, detected from the host if None
+    pub git_config: Vec<(String, String)>, // extra git config keys set on injection, in order
+    pub pinned: bool, // favourites stay on top of the list
+    pub last_used: u64, // unix timestamp of the last injection or clone, 0 if never used
+    pub shared_from: Option<PathBuf>, // read-only store the profile was loaded from, None for own profiles
 }
 
 impl Entry {
-    pub fn to_string(&self) -> String {
-        format!(
+    // the name used for commits, the nickname takes precedence over the username
+    pub fn author_name(&self) -> String {
+        if !self.nickname.is_empty() {
+            self.nickname.clone()
+        } else {
+            self.username.clone()
+        }
+    }
+
+    /*
+     * Parse a single line of the store format, returns None for malformed lines.
+     * The five fixed columns are followed by optional escaped name=value fields,
+     * git config overrides are stored as cfg.<key>=<value>.
+     */
+    pub fn from_line(line: &str) -> Option<Entry> {
+        let profile_split: Vec<&str> = line.split(',').collect();
+        if profile_split.len() < 4 {
+            return None;
+        }
+        let mut git_config = Vec::new();
+        let mut host = String::new();
+        let mut provider = None;
+        let mut pinned = false;
+        let mut last_used = 0;
+        for field in profile_split.iter().skip(5) {
+            let Some((name, value)) = field.split_once('=') else {
+                continue;
+            };
+            let name = store::unescape_field(name);
+            let value = store::unescape_field(value);
+            if let Some(key) = name.strip_prefix("cfg.") {
+                git_config.push((key.to_string(), value));
+            } else if name == "host" {
+                host = value;
+            } else if name == "provider" {
+                provider = Provider::from_label(&value);
+            } else if name == "pinned" {
+                pinned = value == "1";
+            } else if name == "used" {
+                last_used = value.parse().unwrap_or(0);
+            }
+        }
+        Some(Entry {
+            alias: String::from(profile_split[0]),
+            username: String::from(profile_split[1]),
+            email: String::from(profile_split[2]),
+            pa_token: String::from(profile_split[3]),
+            nickname: {
+                if profile_split.len() > 4 {
+                    String::from(profile_split[4])
+                } else {
+                    "".to_string()
+                }
+            },
+            host,
+            provider,
+            git_config,
+            pinned,
+            last_used,
+            shared_from: None,
+        })
+    }
+}
+
+/*
+* Version of the JSON printed by `gat list/show/status --json`. Fields may be
+* added within a version, renaming or removing one bumps it.
+*/
+pub const JSON_VERSION: u32 = 1;
+
+// the JSON form of an Entry, tokens are only included when asked for
+#[derive(Serialize)]
+pub struct EntryJson {
+    pub alias: String,
+    pub username: String,
+    pub email: String,
+    pub nickname: String,
+    pub author_name: String, // what commits are made with
+    pub host: String,
+    pub has_token: bool,
+    pub token: Option<String>, // null unless secrets are shown
+    pub git_config: Vec<ConfigJson>,
+    pub pinned: bool,
+    pub last_used: Option<u64>, // unix timestamp, null if never injected or cloned
+    pub shared_from: Option<PathBuf>, // the read-only store of a shared profile
+}
+
+// output of `gat list --json`
+#[derive(Serialize)]
+pub struct ProfilesJson {
+    pub version: u32,
+    pub profiles: Vec<EntryJson>,
+}
+
+// output of `gat show --json`
+#[derive(Serialize)]
+pub struct ProfileJson {
+    pub version: u32,
+    pub profile: EntryJson,
+}
+
+#[derive(Serialize)]
+pub struct ConfigJson {
+    pub key: String,
+    pub value: String,
+}
+
+impl EntryJson {
+    pub fn new(entry: &Entry, show_secrets: bool) -> EntryJson {
+        EntryJson {
+            alias: entry.alias.clone(),
+            username: entry.username.clone(),
+            email: entry.email.clone(),
+            nickname: entry.nickname.clone(),
+            author_name: entry.author_name(),
+            host: entry.host.clone(),
+            has_token: !entry.pa_token.is_empty(),
+            token: show_secrets.then(|| entry.pa_token.clone()),
+            git_config: entry
+                .git_config
+                .iter()
+                .map(|(key, value)| ConfigJson {
+                    key: key.clone(),
+                    value: value.clone(),
+                })
+                .collect(),
+            pinned: entry.pinned,
+            last_used: (entry.last_used > 0).then_some(entry.last_used),
+            shared_from: entry.shared_from.clone(),
+        }
+    }
+}
+
+// orders the profile list can be sorted in, pinned profiles always come first
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SortOrder {
+    Alias,
+    LastUsed, // most recently injected or cloned first
+    Host,
+}
+
+impl SortOrder {
+    pub fn label(&self) -> &'static str {
+        match self {
+            SortOrder::Alias => "alias",
+            SortOrder::LastUsed => "last used",
+            SortOrder::Host => "host",
+        }
+    }
+}
+
+// the repository the workdir belongs to, shown in the title
+#[derive(Clone, PartialEq, Debug)]
+pub struct TargetRepo {
+    pub root: PathBuf,
+    pub branch: Option<String>, // None while HEAD is detached
+}
+
+// destructive operations of the current session that can be reverted with undo
+pub enum UndoAction {
+    Deleted { index: usize, entry: Entry },
+    Purged { index: usize, trashed: TrashedEntry },
+    Restored { index: usize, trashed: TrashedEntry }, // index in the trash it came from
+}
+
+impl std::fmt::Display for Entry {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
             "{},{},{},{},{}",
             self.alias, self.username, self.email, self.pa_token, self.nickname
-        )
+        )?;
+        if !self.host.is_empty() {
+            write!(f, ",host={}", store::escape_field(&self.host))?;
+        }
+        if let Some(provider) = self.provider {
+            write!(f, ",provider={}", provider.label())?;
+        }
+        if self.pinned {
+            write!(f, ",pinned=1")?;
+        }
+        if self.last_used > 0 {
+            write!(f, ",used={}", self.last_used)?;
+        }
+        for (key, value) in &self.git_config {
+            write!(
+                f,
+                ",{}={}",
+                store::escape_field(&format!("cfg.{}", key)),
+                store::escape_field(value)
+            )?;
+        }
+        Ok(())
     }
 }
 
 pub struct App {
-    pub alias_input: String,
-    pub username_input: String,
-    pub email_input: String,
-    pub token_input: String,
-    pub nickname_input: String,
-    pub clone_url_input: String,
+    pub alias_input: TextInput,
+    pub username_input: TextInput,
+    pub email_input: TextInput,
+    pub token_input: TextInput,
+    pub nickname_input: TextInput,
+    pub host_input: TextInput,
+    pub provider_input: TextInput, // label of the provider, empty detects it from the host
+    pub clone_url_input: TextInput,
+    pub config_key_input: TextInput,
+    pub config_value_input: TextInput,
+    pub config_selected: Option<usize>, // selected override in the ConfigEditor
+    pub scan_root_input: TextInput,
+    pub scan_results: Vec<ScannedRepo>,
+    pub scan_selected: Option<usize>,
+    pub scan_progress: Option<(usize, usize)>, // scanned and total top level dirs, None before a scan
+    pub scan_receiver: Option<Receiver<ScanMessage>>, // set while a scan is running
+    pub scrub_findings: Vec<Finding>,          // what confirming the Scrubbing popup will clean
+    pub scrub_helper: bool, // whether scrubbed repos get gat as credential helper
+    pub new_token_input: TextInput,
+    pub rotate_validate: bool, // whether the new token is checked against the forge first
+    pub rotation_summary: Vec<String>, // report of the last rotation, empty while asking for the token
+    pub import_root_input: TextInput,
+    pub import_candidates: Option<Vec<Candidate>>, // None while asking for the directory
+    pub import_selected: Option<usize>,
+    pub bundle_path_input: TextInput,
+    pub bundle_passphrase_input: TextInput,
+    pub bundle_asks_passphrase: bool, // the bundle is encrypted, the passphrase input is focused
+    pub bundle_incoming: Option<Vec<Incoming>>, // None until a bundle was read
+    pub bundle_selected: Option<usize>,
+    pub repo_path_input: TextInput,
+    pub repo_completions: Vec<String>, // directories matching the last completed path
+    pub coauthors: Vec<String>,        // trailers of the pairing session in the target repo
+    pub coauthor_choice: Vec<String>,  // aliases ticked in the Pairing popup
+    pub pairing_selected: Option<usize>,
+    pub audit_file: PathBuf,
+    pub audit_events: Vec<AuditEvent>, // loaded when the log screen is opened
+    pub audit_filter_input: TextInput,
+    pub audit_selected: Option<usize>, // index into filtered_audit()
+    pub templates: Vec<Template>,
+    pub templates_file: PathBuf,
+    pub template_selected: Option<usize>, // 0 is the blank profile, templates follow
     pub entries: Vec<Entry>,
     pub current_screen: CurrentScreen,
     pub selected_index: Option<usize>,
     pub currently_editing: Option<CurrentlyEditing>,
+    pub editing_index: Option<usize>, // profile changed by the Editing popup, None adds a new one
+    pub click_map: ClickMap,          // clickable regions of the last drawn frame
+    pub last_click: Option<LastClick>,
+    pub sorted_by: Option<SortOrder>, // order last applied with the sort key
+    pub revealed: Option<String>,     // alias whose token the preview shows in the clear
+    pub editing_seed: Entry, // what a new profile takes beyond the inputs, e.g. its git config
     pub save_file: PathBuf,
-    pub workdir: String,
+    pub loaded_content: String, // store content as last read or written by this instance
+    pub status: Option<String>, // feedback for the user, e.g. failed saves
+    pub trash: Vec<TrashedEntry>,
+    pub trash_file: PathBuf,
+    pub trash_selected: Option<usize>,
+    pub repos: Vec<RepoRecord>, // repos profiles were injected into
+    pub repos_file: PathBuf,
+    pub undo_stack: Vec<UndoAction>,
+    pub workdir: PathBuf,
+    pub target: Option<TargetRepo>, // None if the workdir is not inside a repo
+    pub home_dir: PathBuf,
+    pub git: Arc<dyn GitRunner>,
+    pub validator: Arc<dyn TokenValidator>,
+    pub inject_scope: InjectScope,
+    pub injection_plan: Result<Vec<ConfigChange>, String>, // what confirming the Injecting popup will change
+    pub uninject_plan: Result<Vec<Restore>, String>, // what confirming the Removing popup will restore
+    pub dirty: bool, // true while there are changes that have not been written to the store
+    pub in_conflict: bool, // a save found the store changed on disk, until merged or reloaded
+    pub quit_return_screen: CurrentScreen, // screen to go back to if quitting is aborted
     pub closing: bool,
 }
 
 impl App {
-    pub fn new() -> App {
-        let parent_dir = match my_home() {
-            Ok(res) => res,
-            Err(_) => None,
-        };
-        let mut save_file = match parent_dir {
-            Some(dir) => dir,
-            None => panic!("No home dir for the user exists, making profile storage impossible"),
+    /*
+     * Create an app with the store resolved from the flag, the environment or the
+     * config directory, and the profiles of the shared stores added read-only.
+     */
+    pub fn new(store: Option<PathBuf>, shared: Vec<PathBuf>) -> Result<App, String> {
+        let (home_dir, save_file) = App::paths(store)?;
+        if let Some(dir) = save_file.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir).map_err(|err| format!("{}: {}", dir.display(), err))?;
+        }
+        let mut app = App::with_paths(home_dir, save_file, PathBuf::from("."));
+        app.load_shared_stores(&store::shared_stores(shared));
+        Ok(app)
+    }
+
+    /*
+     * The app for the shell hook and prompt, which run on every cd and prompt:
+     * nothing is created, only the profiles and the registry are loaded and git
+     * runs once to find the repo. None outside of repos.
+     */
+    pub fn for_hook(
+        store: Option<PathBuf>,
+        shared: Vec<PathBuf>,
+        workdir: &Path,
+    ) -> Result<Option<App>, String> {
+        let (home_dir, save_file) = App::paths(store)?;
+        let workdir =
+            fs::canonicalize(workdir).map_err(|err| format!("{}: {}", workdir.display(), err))?;
+        let mut app = App::unloaded(home_dir, save_file.clone(), workdir);
+        let toplevel = app
+            .git
+            .run(&app.workdir, &["rev-parse", "--show-toplevel"])
+            .ok()
+            .filter(|output| output.success)
+            .map(|output| output.stdout.trim().to_string())
+            .filter(|root| !root.is_empty());
+        let Some(root) = toplevel else {
+            return Ok(None);
         };
-        save_file.push(".gat");
+        app.target = Some(TargetRepo {
+            root: PathBuf::from(root),
+            branch: None,
+        });
+        app.repos = registry::load_registry(&app.repos_file);
+        app.load_entries(save_file);
+        app.load_shared_stores(&store::shared_stores(shared));
+        Ok(Some(app))
+    }
+
+    // the home directory and the store to use, nothing is created yet
+    fn paths(store: Option<PathBuf>) -> Result<(PathBuf, PathBuf), String> {
+        let home_dir = my_home().ok().flatten();
+        let save_file = store::resolve_store(
+            store,
+            env::var_os(store::STORE_VAR).map(PathBuf::from),
+            env::var_os("XDG_CONFIG_HOME").map(PathBuf::from),
+            home_dir.as_deref(),
+        )?;
+        // without a home directory the global git config is looked for next to the store
+        let home_dir = home_dir
+            .or_else(|| save_file.parent().map(Path::to_path_buf))
+            .unwrap_or_default();
+        Ok((home_dir, save_file))
+    }
+
+    /*
+     * Create an app using the given home directory, store and target repository,
+     * used directly by tests.
+     */
+    pub fn with_paths(home_dir: PathBuf, save_file: PathBuf, workdir: PathBuf) -> App {
         if !save_file.exists() {
-            let _ = File::create(&save_file);
-        }
-        let mut app = App {
-            alias_input: String::new(),
-            username_input: String::new(),
-            email_input: String::new(),
-            token_input: String::new(),
-            nickname_input: String::new(),
-            clone_url_input: String::new(),
+            let _ = store::create_private(&save_file);
+        }
+        let mut app = App::unloaded(home_dir, save_file.clone(), workdir);
+        app.templates = template::load_templates(&app.templates_file);
+        app.trash = trash::load_trash(&app.trash_file, trash::retention_days());
+        app.repos = registry::load_registry(&app.repos_file);
+        app.load_entries(save_file);
+        app
+    }
+
+    // an app without any profiles, trash, templates or registered repos loaded
+    fn unloaded(home_dir: PathBuf, save_file: PathBuf, workdir: PathBuf) -> App {
+        App {
+            alias_input: TextInput::default(),
+            username_input: TextInput::default(),
+            email_input: TextInput::default(),
+            token_input: TextInput::masked(),
+            nickname_input: TextInput::default(),
+            host_input: TextInput::default(),
+            provider_input: TextInput::default(),
+            clone_url_input: TextInput::default(),
+            config_key_input: TextInput::default(),
+            config_value_input: TextInput::default(),
+            config_selected: None,
+            scan_root_input: TextInput::default(),
+            scan_results: Vec::new(),
+            scan_selected: None,
+            scan_progress: None,
+            scan_receiver: None,
+            scrub_findings: Vec::new(),
+            scrub_helper: true,
+            new_token_input: TextInput::default(),
+            rotate_validate: true,
+            rotation_summary: Vec::new(),
+            import_root_input: TextInput::default(),
+            import_candidates: None,
+            import_selected: None,
+            bundle_path_input: TextInput::default(),
+            bundle_passphrase_input: TextInput::masked(),
+            bundle_asks_passphrase: false,
+            bundle_incoming: None,
+            bundle_selected: None,
+            repo_path_input: TextInput::default(),
+            repo_completions: Vec::new(),
+            coauthors: Vec::new(),
+            coauthor_choice: Vec::new(),
+            pairing_selected: None,
+            audit_file: audit::audit_file(&save_file),
+            audit_events: Vec::new(),
+            audit_filter_input: TextInput::default(),
+            audit_selected: None,
+            templates: Vec::new(),
+            templates_file: template::templates_file(&save_file),
+            template_selected: None,
             entries: Vec::new(),
             current_screen: CurrentScreen::Main,
             selected_index: None,
             currently_editing: None,
+            editing_index: None,
+            click_map: ClickMap::default(),
+            last_click: None,
+            sorted_by: None,
+            revealed: None,
+            editing_seed: Entry::default(),
             save_file: save_file.clone(),
-            workdir: ".".to_string(),
+            loaded_content: String::new(),
+            status: None,
+            trash: Vec::new(),
+            trash_file: trash::trash_file(&save_file),
+            trash_selected: None,
+            repos: Vec::new(),
+            repos_file: registry::registry_file(&save_file),
+            undo_stack: Vec::new(),
+            workdir,
+            target: None,
+            home_dir,
+            git: Arc::new(SystemGit),
+            validator: Arc::new(ForgeApi),
+            inject_scope: InjectScope::Local,
+            injection_plan: Ok(Vec::new()),
+            uninject_plan: Ok(Vec::new()),
+            dirty: false,
+            in_conflict: false,
+            quit_return_screen: CurrentScreen::Main,
             closing: false,
-        };
-        app.load_entries(save_file);
-        app
+        }
     }
 
     pub fn load_entries(&mut self, save_file: PathBuf) {
-        let content = fs::read_to_string(save_file).expect("unable to read file");
-        for line in content.lines() {
-            if line.len() < 1 {
-                continue;
+        let content = match fs::read_to_string(&save_file) {
+            Ok(content) => content,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(err) => {
+                self.status = Some(format!("Unable to read {}: {}", save_file.display(), err));
+                String::new()
+            }
+        };
+        self.entries = store::parse_entries(&content);
+        self.loaded_content = content;
+    }
+
+    /*
+     * Add the profiles of other stores, e.g. one shared by a team. They are listed
+     * after the own profiles, can be used but not changed, and are never written
+     * to the own store. Aliases that are already taken are shadowed.
+     */
+    pub fn load_shared_stores(&mut self, paths: &[PathBuf]) {
+        for path in paths {
+            let content = match fs::read_to_string(path) {
+                Ok(content) => content,
+                Err(err) => {
+                    self.status = Some(format!(
+                        "Unable to read the shared store {}: {}",
+                        path.display(),
+                        err
+                    ));
+                    continue;
+                }
             };
-            let profile_split: Vec<&str> = line.split(',').collect();
-            if profile_split.len() < 4 {
-                continue;
+            for mut entry in store::parse_entries(&content) {
+                if self.entries.iter().any(|known| known.alias == entry.alias) {
+                    continue;
+                }
+                entry.shared_from = Some(path.clone());
+                self.entries.push(entry);
             }
-            self.entries.push(Entry {
-                alias: String::from(profile_split[0]),
-                username: String::from(profile_split[1]),
-                email: String::from(profile_split[2]),
-                pa_token: String::from(profile_split[3]),
-                nickname: {
-                    if profile_split.len() > 4 {
-                        String::from(profile_split[4])
-                    } else {
-                        "".to_string()
-                    }
-                },
-            });
         }
     }
 
+    // the profiles of the own store, the ones written on save
+    pub fn own_entries(&self) -> Vec<Entry> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.shared_from.is_none())
+            .cloned()
+            .collect()
+    }
+
+    /*
+     * Change the own profiles with the shared ones set aside, so additions end up
+     * in front of the shared profiles.
+     */
+    fn with_own_entries<T>(&mut self, change: impl FnOnce(&mut Vec<Entry>) -> T) -> T {
+        let (shared, mut own): (Vec<Entry>, Vec<Entry>) = self
+            .entries
+            .drain(..)
+            .partition(|entry| entry.shared_from.is_some());
+        let result = change(&mut own);
+        own.extend(shared);
+        self.entries = own;
+        result
+    }
+
+    // the store holding the profile, which is where gat credential looks it up
+    pub fn store_of(&self, alias: &str) -> &Path {
+        self.entries
+            .iter()
+            .find(|entry| entry.alias == alias)
+            .and_then(|entry| entry.shared_from.as_deref())
+            .unwrap_or(&self.save_file)
+    }
+
+    /*
+     * Whether the selected profile may be changed, shared profiles are read-only.
+     */
+    pub fn selected_is_writable(&mut self) -> bool {
+        let Some(idx) = self.selected_index else {
+            return true;
+        };
+        match &self.entries[idx].shared_from {
+            Some(path) => {
+                self.status = Some(format!(
+                    "{} comes from the shared store {} and is read-only",
+                    self.entries[idx].alias,
+                    path.display()
+                ));
+                false
+            }
+            None => true,
+        }
+    }
+
+    /*
+     * Move the selected profile one place up or down. Profiles stay within their
+     * group, pinned or not, and shared profiles keep their place after the own ones.
+     */
+    pub fn move_selected(&mut self, down: bool) {
+        let Some(idx) = self.selected_index else {
+            return;
+        };
+        if !self.selected_is_writable() {
+            return;
+        }
+        let target = if down {
+            idx + 1
+        } else if idx > 0 {
+            idx - 1
+        } else {
+            return;
+        };
+        let Some(neighbour) = self.entries.get(target) else {
+            return;
+        };
+        if neighbour.shared_from.is_some() || neighbour.pinned != self.entries[idx].pinned {
+            return;
+        }
+        self.entries.swap(idx, target);
+        self.selected_index = Some(target);
+        self.mark_dirty();
+    }
+
+    // show or hide the token of the selected profile in the preview
+    pub fn toggle_reveal(&mut self) {
+        let Some(idx) = self.selected_index else {
+            return;
+        };
+        let alias = self.entries[idx].alias.clone();
+        if self.revealed.as_deref() == Some(&alias) {
+            self.revealed = None;
+        } else {
+            if !self.entries[idx].pa_token.is_empty() {
+                self.audit(Action::Reveal, &alias, None, "preview");
+            }
+            self.revealed = Some(alias);
+        }
+    }
+
+    /*
+     * Pin or unpin the selected profile. Pinning moves it below the other pinned
+     * profiles, unpinning right below them.
+     */
+    pub fn toggle_pin(&mut self) {
+        let Some(idx) = self.selected_index else {
+            return;
+        };
+        if !self.selected_is_writable() {
+            return;
+        }
+        let mut entry = self.entries.remove(idx);
+        entry.pinned = !entry.pinned;
+        let alias = entry.alias.clone();
+        self.with_own_entries(|own| {
+            let at = own.iter().take_while(|entry| entry.pinned).count();
+            own.insert(at, entry);
+        });
+        self.select_alias(&alias);
+        self.mark_dirty();
+    }
+
+    // sort the own profiles by the order following the one applied last
+    pub fn cycle_sort(&mut self) {
+        let order = match self.sorted_by {
+            None | Some(SortOrder::Host) => SortOrder::Alias,
+            Some(SortOrder::Alias) => SortOrder::LastUsed,
+            Some(SortOrder::LastUsed) => SortOrder::Host,
+        };
+        self.sort_entries(order);
+        self.status = Some(format!("Sorted by {}", order.label()));
+    }
+
+    /*
+     * Sort the own profiles, keeping pinned ones on top. The result is stored,
+     * so the order stays until the next move or sort. Profiles without a host
+     * go last when sorting by host.
+     */
+    pub fn sort_entries(&mut self, order: SortOrder) {
+        let selected = self
+            .selected_index
+            .map(|idx| self.entries[idx].alias.clone());
+        self.with_own_entries(|own| {
+            own.sort_by(|a, b| {
+                b.pinned.cmp(&a.pinned).then_with(|| match order {
+                    SortOrder::Alias => a.alias.to_lowercase().cmp(&b.alias.to_lowercase()),
+                    SortOrder::LastUsed => b.last_used.cmp(&a.last_used),
+                    SortOrder::Host => (a.host.is_empty(), a.host.to_lowercase())
+                        .cmp(&(b.host.is_empty(), b.host.to_lowercase())),
+                })
+            })
+        });
+        if let Some(alias) = selected {
+            self.select_alias(&alias);
+        }
+        self.sorted_by = Some(order);
+        self.mark_dirty();
+    }
+
+    fn select_alias(&mut self, alias: &str) {
+        self.selected_index = self.entries.iter().position(|entry| entry.alias == alias);
+    }
+
     pub fn toggle_editing(&mut self) {
         if let Some(edit_mode) = &self.currently_editing {
             match edit_mode {
@@ -127,123 +741,1449 @@ impl App {
                 CurrentlyEditing::Token => {
                     self.currently_editing = Some(CurrentlyEditing::Nickname)
                 }
-                CurrentlyEditing::Nickname => {
+                CurrentlyEditing::Nickname => self.currently_editing = Some(CurrentlyEditing::Host),
+                CurrentlyEditing::Host => self.currently_editing = Some(CurrentlyEditing::Provider),
+                CurrentlyEditing::Provider => {
                     self.currently_editing = Some(CurrentlyEditing::Alias)
                 }
+                CurrentlyEditing::ConfigKey => {
+                    self.currently_editing = Some(CurrentlyEditing::ConfigValue)
+                }
+                CurrentlyEditing::ConfigValue => {
+                    self.currently_editing = Some(CurrentlyEditing::ConfigKey)
+                }
             };
         } else {
             self.currently_editing = Some(CurrentlyEditing::Alias);
         }
     }
 
+    /*
+     * Open the Editing popup prefilled with the selected profile.
+     */
+    pub fn edit_selected_entry(&mut self) {
+        let Some(idx) = self.selected_index else {
+            return;
+        };
+        if !self.selected_is_writable() {
+            return;
+        }
+        let entry = self.entries[idx].clone();
+        self.fill_editor(&entry);
+        self.editing_index = Some(idx);
+    }
+
+    fn fill_editor(&mut self, entry: &Entry) {
+        self.alias_input.set(&entry.alias);
+        self.username_input.set(&entry.username);
+        self.email_input.set(&entry.email);
+        // a seeded email like @example.com waits for its local part
+        if entry.email.starts_with('@') {
+            self.email_input.move_to_start();
+        }
+        self.token_input.set(&entry.pa_token);
+        // like the preview, the token shows in the clear only once revealed
+        self.token_input
+            .set_masked(self.revealed.as_deref() != Some(entry.alias.as_str()));
+        self.nickname_input.set(&entry.nickname);
+        self.host_input.set(&entry.host);
+        self.provider_input
+            .set(entry.provider.map_or("", |provider| provider.label()));
+        self.current_screen = CurrentScreen::Editing;
+        self.currently_editing = Some(CurrentlyEditing::Alias);
+    }
+
+    /*
+     * Open the Editing popup for a new profile pre-filled from the seed, which
+     * also passes on the git config the popup does not show.
+     */
+    fn create_from(&mut self, seed: Entry) {
+        self.fill_editor(&seed);
+        self.editing_index = None;
+        self.editing_seed = seed;
+    }
+
+    // start a new profile, from a template if there are any
+    pub fn new_profile(&mut self) {
+        if self.templates.is_empty() {
+            self.create_from(Entry::default());
+        } else {
+            self.template_selected = Some(0);
+            self.current_screen = CurrentScreen::ChoosingTemplate;
+        }
+    }
+
+    pub fn choose_template(&mut self) {
+        let seed = match self.template_selected {
+            Some(idx) if idx > 0 => self.templates[idx - 1].seed(),
+            _ => Entry::default(),
+        };
+        self.create_from(seed);
+    }
+
+    /*
+     * Start a new profile as a copy of the selected one, e.g. for the same account
+     * on another forge. Shared profiles can be copied into the own store this way.
+     */
+    pub fn duplicate_selected_entry(&mut self) {
+        let Some(idx) = self.selected_index else {
+            return;
+        };
+        let entry = &self.entries[idx];
+        let taken: Vec<String> = self
+            .entries
+            .iter()
+            .map(|entry| entry.alias.clone())
+            .collect();
+        let seed = Entry {
+            alias: discover::unique_alias(&format!("{}-copy", entry.alias), &taken),
+            shared_from: None,
+            ..entry.clone()
+        };
+        self.create_from(seed);
+    }
+
+    // keep what the selected profile shares with others of its kind as a template
+    pub fn save_template_from_selected(&mut self) {
+        let Some(idx) = self.selected_index else {
+            self.status = Some("Select the profile to make a template of first".to_string());
+            return;
+        };
+        let template = Template::from_entry(&self.entries[idx]);
+        self.status = Some(format!("Saved template {}", template.name));
+        match self
+            .templates
+            .iter()
+            .position(|existing| existing.name == template.name)
+        {
+            Some(existing) => self.templates[existing] = template,
+            None => self.templates.push(template),
+        }
+        self.save_templates();
+    }
+
+    pub fn delete_selected_template(&mut self) {
+        let Some(idx) = self.template_selected.filter(|idx| *idx > 0) else {
+            return;
+        };
+        let template = self.templates.remove(idx - 1);
+        self.status = Some(format!("Deleted template {}", template.name));
+        self.template_selected = Some(idx.min(self.templates.len()));
+        self.save_templates();
+    }
+
+    fn save_templates(&mut self) {
+        if let Err(err) = template::save_templates(&self.templates_file, &self.templates) {
+            self.status = Some(format!("Saving the templates failed: {}", err));
+        }
+    }
+
+    /*
+     * Save the Editing popup, either as a new profile or over the edited one.
+     * When the edit changes the identity of a profile that is used in known repos,
+     * the user is asked whether to inject it there again.
+     */
     pub fn store_entries(&mut self) {
-        let created_entry = Entry {
-            alias: self.alias_input.clone(),
-            username: self.username_input.clone(),
-            email: self.email_input.clone(),
-            pa_token: self.token_input.clone(),
-            nickname: self.nickname_input.clone(),
+        let label = self.provider_input.value().trim();
+        let provider = if label.is_empty() {
+            None
+        } else if let Some(provider) = Provider::from_label(label) {
+            Some(provider)
+        } else {
+            self.status = Some(format!(
+                "Unknown provider {}, use one of: {}",
+                label,
+                Provider::ALL.map(|provider| provider.label()).join(", ")
+            ));
+            self.current_screen = CurrentScreen::Editing;
+            self.currently_editing = Some(CurrentlyEditing::Provider);
+            return;
         };
-        self.entries.push(created_entry);
+        // the alias names the profile in the registry, helpers, the audit log and undo
+        let alias = self.alias_input.value();
+        let duplicate = self
+            .entries
+            .iter()
+            .enumerate()
+            .any(|(idx, entry)| Some(idx) != self.editing_index && entry.alias == alias);
+        if alias.trim().is_empty() || duplicate {
+            self.status = Some(if duplicate {
+                format!("A profile named {} already exists", alias)
+            } else {
+                "The alias must not be empty".to_string()
+            });
+            self.current_screen = CurrentScreen::Editing;
+            self.currently_editing = Some(CurrentlyEditing::Alias);
+            return;
+        }
+        let mut created_entry = Entry {
+            alias: self.alias_input.value().to_string(),
+            username: self.username_input.value().to_string(),
+            email: self.email_input.value().to_string(),
+            pa_token: self.token_input.value().to_string(),
+            nickname: self.nickname_input.value().to_string(),
+            host: self.host_input.value().trim().to_string(),
+            provider,
+            git_config: self.editing_seed.git_config.clone(),
+            ..Entry::default()
+        };
+        let Some(idx) = self.editing_index.take() else {
+            self.audit(Action::Create, &created_entry.alias, None, "");
+            self.with_own_entries(|own| own.push(created_entry));
+            self.mark_dirty();
+            return;
+        };
+        let previous = &self.entries[idx];
+        created_entry.git_config = previous.git_config.clone();
+        created_entry.pinned = previous.pinned;
+        created_entry.last_used = previous.last_used;
+        let identity_changed = previous.author_name() != created_entry.author_name()
+            || previous.email != created_entry.email;
+        let detail = if previous.alias != created_entry.alias {
+            format!("renamed from {}", previous.alias)
+        } else {
+            String::new()
+        };
+        self.audit(Action::Edit, &created_entry.alias, None, &detail);
+        let old_alias = self.entries[idx].alias.clone();
+        if old_alias != created_entry.alias {
+            for record in &mut self.repos {
+                if record.alias == old_alias {
+                    record.alias = created_entry.alias.clone();
+                }
+            }
+            for repo in &mut self.scan_results {
+                if repo.profile.as_deref() == Some(old_alias.as_str()) {
+                    repo.profile = Some(created_entry.alias.clone());
+                }
+            }
+            self.save_registry();
+            // helpers written by scrubbing and cloning ask for the profile by alias
+            for repo in self.known_repos() {
+                // the workdir need not be a repo
+                let _ = scrub::rename_helpers(
+                    self.git.as_ref(),
+                    &repo,
+                    &old_alias,
+                    &created_entry.alias,
+                );
+            }
+        }
+        self.entries[idx] = created_entry;
+        self.mark_dirty();
+        if identity_changed && !self.repos_of(idx).is_empty() {
+            self.selected_index = Some(idx);
+            self.current_screen = CurrentScreen::Propagating;
+        }
+    }
+
+    /*
+     * The text input receiving key presses and pastes on the current screen, if any.
+     */
+    pub fn active_input_mut(&mut self) -> Option<&mut TextInput> {
+        match self.current_screen {
+            CurrentScreen::Cloning => Some(&mut self.clone_url_input),
+            CurrentScreen::Editing => match self.currently_editing.as_ref()? {
+                CurrentlyEditing::Alias => Some(&mut self.alias_input),
+                CurrentlyEditing::Username => Some(&mut self.username_input),
+                CurrentlyEditing::Email => Some(&mut self.email_input),
+                CurrentlyEditing::Token => Some(&mut self.token_input),
+                CurrentlyEditing::Nickname => Some(&mut self.nickname_input),
+                CurrentlyEditing::Host => Some(&mut self.host_input),
+                CurrentlyEditing::Provider => Some(&mut self.provider_input),
+                _ => None,
+            },
+            CurrentScreen::Rotating if self.rotation_summary.is_empty() => {
+                Some(&mut self.new_token_input)
+            }
+            CurrentScreen::Bundle if self.bundle_incoming.is_none() => {
+                if self.bundle_asks_passphrase {
+                    Some(&mut self.bundle_passphrase_input)
+                } else {
+                    Some(&mut self.bundle_path_input)
+                }
+            }
+            CurrentScreen::ChoosingRepo => Some(&mut self.repo_path_input),
+            CurrentScreen::AuditLog => Some(&mut self.audit_filter_input),
+            CurrentScreen::Importing if self.import_candidates.is_none() => {
+                Some(&mut self.import_root_input)
+            }
+            CurrentScreen::Scanning if self.scan_progress.is_none() => {
+                Some(&mut self.scan_root_input)
+            }
+            CurrentScreen::ConfigEditor => match self.currently_editing.as_ref()? {
+                CurrentlyEditing::ConfigKey => Some(&mut self.config_key_input),
+                CurrentlyEditing::ConfigValue => Some(&mut self.config_value_input),
+                _ => None,
+            },
+            _ => None,
+        }
     }
 
     pub fn clear(&mut self) {
-        self.alias_input = String::new();
-        self.username_input = String::new();
-        self.email_input = String::new();
-        self.token_input = String::new();
-        self.nickname_input = String::new();
-        self.clone_url_input = String::new();
+        self.alias_input.clear();
+        self.username_input.clear();
+        self.email_input.clear();
+        self.token_input.clear();
+        self.token_input.set_masked(true);
+        self.nickname_input.clear();
+        self.host_input.clear();
+        self.provider_input.clear();
+        self.clone_url_input.clear();
+        self.config_key_input.clear();
+        self.config_value_input.clear();
+        self.new_token_input.clear();
         self.currently_editing = None;
+        self.editing_index = None;
+        self.editing_seed = Entry::default();
     }
 
     pub fn str_from_entry(&self) -> String {
         match self.selected_index {
             None => String::new(),
-            Some(idx) => format!(
-                "Username: {}\n\nEmail: {}\n\nToken: {}\n\nNickname: {}",
-                self.entries[idx].username,
-                self.entries[idx].email,
-                self.entries[idx].pa_token,
-                self.entries[idx].nickname
-            ),
+            Some(idx) => {
+                // tokens stay hidden until revealed, which is audited
+                let token = if self.entries[idx].pa_token.is_empty()
+                    || self.revealed.as_deref() == Some(&self.entries[idx].alias)
+                {
+                    self.entries[idx].pa_token.clone()
+                } else {
+                    "******** (v to reveal)".to_string()
+                };
+                let mut preview = format!(
+                    "Username: {}\n\nEmail: {}\n\nToken: {}\n\nNickname: {}",
+                    self.entries[idx].username,
+                    self.entries[idx].email,
+                    token,
+                    self.entries[idx].nickname
+                );
+                if !self.entries[idx].host.is_empty() {
+                    preview.push_str(&format!("\n\nHost: {}", self.entries[idx].host));
+                }
+                if self.entries[idx].last_used > 0 {
+                    preview.push_str(&format!(
+                        "\n\nLast used: {}",
+                        audit::format_time(self.entries[idx].last_used)
+                    ));
+                }
+                match self.entries[idx].provider {
+                    Some(provider) => {
+                        preview.push_str(&format!("\n\nProvider: {}", provider.label()))
+                    }
+                    None if !self.entries[idx].host.is_empty() => preview.push_str(&format!(
+                        "\n\nProvider: {} (detected)",
+                        Provider::detect(&self.entries[idx].host).label()
+                    )),
+                    None => {}
+                }
+                if !self.entries[idx].git_config.is_empty() {
+                    preview.push_str("\n\nGit config:");
+                    for (key, value) in &self.entries[idx].git_config {
+                        preview.push_str(&format!("\n  {} = {}", key, value));
+                    }
+                }
+                let repos = self.repos_of(idx);
+                if !repos.is_empty() {
+                    let plural = if repos.len() == 1 { "" } else { "s" };
+                    preview.push_str(&format!("\n\nUsed in {} repo{}:", repos.len(), plural));
+                    for record in repos {
+                        preview.push_str(&format!(
+                            "\n  {} ({})",
+                            record.path.display(),
+                            record.scope.label()
+                        ));
+                    }
+                }
+                preview
+            }
+        }
+    }
+
+    /*
+     * Add the key and value typed into the ConfigEditor to the selected profile.
+     * Setting a key that already exists replaces its value in place.
+     */
+    pub fn add_config_override(&mut self) {
+        let Some(idx) = self.selected_index else {
+            return;
+        };
+        let key = self.config_key_input.value().trim().to_string();
+        if key.is_empty() {
+            return;
+        }
+        let value = self.config_value_input.value().to_string();
+        // only the key, values like http.extraHeader may carry secrets
+        let alias = self.entries[idx].alias.clone();
+        self.audit(Action::Edit, &alias, None, &format!("set {}", key));
+        let git_config = &mut self.entries[idx].git_config;
+        match git_config.iter().position(|(existing, _)| *existing == key) {
+            Some(pos) => git_config[pos].1 = value,
+            None => git_config.push((key, value)),
+        }
+        self.config_selected = Some(git_config.len() - 1);
+        self.mark_dirty();
+    }
+
+    pub fn remove_selected_config_override(&mut self) {
+        let (Some(idx), Some(config_idx)) = (self.selected_index, self.config_selected) else {
+            return;
+        };
+        if config_idx >= self.entries[idx].git_config.len() {
+            return;
         }
+        let (key, _) = self.entries[idx].git_config.remove(config_idx);
+        let alias = self.entries[idx].alias.clone();
+        self.audit(Action::Edit, &alias, None, &format!("unset {}", key));
+        self.config_selected = self.entries[idx]
+            .git_config
+            .len()
+            .checked_sub(1)
+            .map(|last| last.min(config_idx));
+        self.mark_dirty();
     }
 
     pub fn delete_current_entry(&mut self) {
-        match self.selected_index {
-            Some(idx) => {
-                self.entries.remove(idx);
-                if self.entries.len() <= idx {
-                    self.selected_index = None;
+        if let Some(idx) = self.selected_index {
+            let entry = self.entries.remove(idx);
+            self.audit(Action::Delete, &entry.alias, None, "");
+            if self.entries.len() <= idx {
+                self.selected_index = None;
+            }
+            self.trash.push(TrashedEntry {
+                entry: entry.clone(),
+                deleted_at: trash::now(),
+            });
+            self.undo_stack
+                .push(UndoAction::Deleted { index: idx, entry });
+            self.mark_dirty();
+        }
+    }
+
+    /*
+     * Revert the most recent destructive operation of this session.
+     */
+    pub fn undo(&mut self) {
+        let Some(action) = self.undo_stack.pop() else {
+            return;
+        };
+        match action {
+            UndoAction::Deleted { index, entry } => {
+                // already back, e.g. restored from the trash meanwhile
+                if self
+                    .entries
+                    .iter()
+                    .any(|existing| existing.alias == entry.alias)
+                {
+                    return;
+                }
+                let line = entry.to_string();
+                if let Some(pos) = self
+                    .trash
+                    .iter()
+                    .rposition(|trashed| trashed.entry.to_string() == line)
+                {
+                    self.trash.remove(pos);
                 }
+                self.audit(Action::Restore, &entry.alias, None, "undo");
+                let index = self.with_own_entries(|own| {
+                    let index = index.min(own.len());
+                    own.insert(index, entry);
+                    index
+                });
+                self.selected_index = Some(index);
             }
-            None => {}
+            UndoAction::Purged { index, trashed } => {
+                let index = index.min(self.trash.len());
+                self.trash.insert(index, trashed);
+                self.trash_selected = Some(index);
+            }
+            UndoAction::Restored { index, trashed } => {
+                let line = trashed.entry.to_string();
+                let removed = self.with_own_entries(|own| {
+                    let pos = own.iter().rposition(|entry| entry.to_string() == line)?;
+                    Some(own.remove(pos))
+                });
+                // changed since it was restored, leave it where it is
+                if removed.is_none() {
+                    return;
+                }
+                self.audit(Action::Delete, &trashed.entry.alias, None, "undo");
+                let index = index.min(self.trash.len());
+                self.trash.insert(index, trashed);
+                self.trash_selected = Some(index);
+                self.selected_index = None;
+            }
+        }
+        self.mark_dirty();
+    }
+
+    pub fn restore_selected_trash(&mut self) {
+        let Some(idx) = self.trash_selected else {
+            return;
+        };
+        let trashed = self.trash.remove(idx);
+        self.audit(Action::Restore, &trashed.entry.alias, None, "trash");
+        self.undo_stack.push(UndoAction::Restored {
+            index: idx,
+            trashed: TrashedEntry {
+                entry: trashed.entry.clone(),
+                deleted_at: trashed.deleted_at,
+            },
+        });
+        let index = self.with_own_entries(|own| {
+            own.push(trashed.entry);
+            own.len() - 1
+        });
+        self.selected_index = Some(index);
+        if self.trash.len() <= idx {
+            self.trash_selected = self.trash.len().checked_sub(1);
         }
+        self.mark_dirty();
     }
 
-    fn exec_cmd(&self, command: String) {
-        let _ = if cfg!(target_os = "windows") {
-            Command::new("cmd")
-                .args(["/C", &command])
-                .current_dir(self.workdir.clone())
-                .output()
-                .expect("failed to execute process")
+    // remove the selected profile from the trash for good, can still be undone this session
+    pub fn purge_selected_trash(&mut self) {
+        let Some(idx) = self.trash_selected else {
+            return;
+        };
+        let trashed = self.trash.remove(idx);
+        self.undo_stack.push(UndoAction::Purged {
+            index: idx,
+            trashed,
+        });
+        if self.trash.len() <= idx {
+            self.trash_selected = self.trash.len().checked_sub(1);
+        }
+        self.mark_dirty();
+    }
+
+    /*
+     * Run git in the given directory. Failures are reported through the status line.
+     */
+    fn run_git(&mut self, workdir: &Path, args: &[&str]) -> Option<GitOutput> {
+        match self.git.run(workdir, args) {
+            Ok(output) if output.success => Some(output),
+            Ok(output) => {
+                self.status = Some(format!("git {} failed: {}", args[0], output.stderr.trim()));
+                None
+            }
+            Err(err) => {
+                self.status = Some(format!("Unable to run git: {}", err));
+                None
+            }
+        }
+    }
+
+    /*
+     * Work out what injecting the selected profile with the chosen scope will change,
+     * shown in the Injecting popup.
+     */
+    pub fn refresh_injection_plan(&mut self) {
+        let Some(idx) = self.selected_index else {
+            self.injection_plan = Err("No profile selected".to_string());
+            return;
+        };
+        self.injection_plan = inject::plan(
+            self.git.as_ref(),
+            &self.workdir,
+            &self.home_dir,
+            &self.entries[idx],
+            self.inject_scope,
+        );
+    }
+
+    /*
+     * Work out which keys gat wrote in the chosen scope and what removing them
+     * will restore, shown in the Removing popup.
+     */
+    pub fn refresh_uninject_plan(&mut self) {
+        self.uninject_plan = inject::plan_uninject(
+            self.git.as_ref(),
+            &self.workdir,
+            &self.home_dir,
+            self.inject_scope,
+        );
+    }
+
+    pub fn cycle_inject_scope(&mut self) {
+        self.inject_scope = self.inject_scope.next();
+        if self.current_screen == CurrentScreen::Removing {
+            self.refresh_uninject_plan();
+        } else {
+            self.refresh_injection_plan();
+        }
+    }
+
+    /*
+     * Reset the repo to the state before gat injected a profile into the chosen
+     * scope, only touching the keys gat wrote itself.
+     */
+    pub fn uninject(&mut self) {
+        let result = inject::plan_uninject(
+            self.git.as_ref(),
+            &self.workdir,
+            &self.home_dir,
+            self.inject_scope,
+        )
+        .and_then(|restores| {
+            let alias = inject::injected_profile(self.git.as_ref(), &self.workdir, &restores);
+            inject::uninject(self.git.as_ref(), &self.workdir, &restores)?;
+            Ok(alias)
+        });
+        match result {
+            Ok(Some(alias)) => {
+                let workdir = self.workdir.clone();
+                let repo = (self.inject_scope != InjectScope::Global).then_some(workdir.as_path());
+                self.audit(Action::Remove, &alias, repo, self.inject_scope.label());
+            }
+            Ok(None) => {}
+            Err(err) => self.status = Some(err),
+        }
+    }
+
+    pub fn inject_selected_profile(&mut self) {
+        let Some(idx) = self.selected_index else {
+            return;
+        };
+        let workdir = self.workdir.clone();
+        self.inject_profile(idx, self.inject_scope, &workdir);
+    }
+
+    /*
+     * Inject a profile into the repo at workdir. Every injection, single or in bulk,
+     * goes through here. Keys gat wrote into the scope before that the profile does
+     * not set are removed first. Returns whether it succeeded, failures end up in
+     * the status.
+     */
+    pub fn inject_profile(&mut self, idx: usize, scope: InjectScope, workdir: &Path) -> bool {
+        let result = inject::plan(
+            self.git.as_ref(),
+            workdir,
+            &self.home_dir,
+            &self.entries[idx],
+            scope,
+        )
+        .and_then(|changes| {
+            let written = inject::plan_uninject(self.git.as_ref(), workdir, &self.home_dir, scope)?;
+            inject::uninject(
+                self.git.as_ref(),
+                workdir,
+                &inject::stale(written, &changes),
+            )?;
+            inject::apply(
+                self.git.as_ref(),
+                workdir,
+                &self.entries[idx].alias,
+                &changes,
+            )
+        });
+        match result {
+            Ok(()) => {
+                let alias = self.entries[idx].alias.clone();
+                self.entries[idx].last_used = trash::now();
+                self.mark_dirty();
+                // global injections are not tied to a repo
+                if scope != InjectScope::Global {
+                    registry::record(&mut self.repos, &alias, scope, workdir);
+                    self.save_registry();
+                    self.audit(Action::Inject, &alias, Some(workdir), scope.label());
+                } else {
+                    self.audit(Action::Inject, &alias, None, scope.label());
+                }
+                true
+            }
+            Err(err) => {
+                self.status = Some(err);
+                false
+            }
+        }
+    }
+
+    // known repos the profile was injected into, most recently used first
+    pub fn repos_of(&self, idx: usize) -> Vec<&RepoRecord> {
+        let mut repos: Vec<&RepoRecord> = self
+            .repos
+            .iter()
+            .filter(|record| record.alias == self.entries[idx].alias)
+            .collect();
+        repos.sort_by_key(|record| std::cmp::Reverse(record.last_used));
+        repos
+    }
+
+    /*
+     * Inject the selected profile again into every known repo using it, with the
+     * scope it was injected with before. Repos that vanished are dropped first.
+     */
+    pub fn propagate_selected_profile(&mut self) {
+        let Some(idx) = self.selected_index else {
+            return;
+        };
+        self.repos.retain(|record| record.path.exists());
+        let targets: Vec<(InjectScope, PathBuf)> = self
+            .repos_of(idx)
+            .into_iter()
+            .map(|record| (record.scope, record.path.clone()))
+            .collect();
+        let mut failed = 0;
+        for (scope, path) in &targets {
+            if !self.inject_profile(idx, *scope, path) {
+                failed += 1;
+            }
+        }
+        if failed > 0 {
+            self.status = Some(format!(
+                "Updating {} of {} repos failed",
+                failed,
+                targets.len()
+            ));
+        }
+        self.save_registry();
+    }
+
+    fn save_registry(&mut self) {
+        if let Err(err) = registry::save_registry(&self.repos_file, &self.repos) {
+            self.status = Some(format!("Saving the repo registry failed: {}", err));
+        }
+    }
+
+    // first profile bound to the given remote host
+    pub fn suggest_profile(&self, host: Option<&str>) -> Option<usize> {
+        let host = host?;
+        self.entries
+            .iter()
+            .position(|entry| !entry.host.is_empty() && entry.host.eq_ignore_ascii_case(host))
+    }
+
+    pub fn open_scan(&mut self) {
+        if self.scan_progress.is_none() && self.scan_root_input.value().is_empty() {
+            self.scan_root_input
+                .insert_str(&self.home_dir.join("src").to_string_lossy());
+        }
+        self.current_screen = CurrentScreen::Scanning;
+    }
+
+    pub fn start_scan(&mut self) {
+        let root = PathBuf::from(self.scan_root_input.value());
+        if !root.is_dir() {
+            self.status = Some(format!("{} is not a directory", root.display()));
+            return;
+        }
+        self.scan_results.clear();
+        self.scan_selected = None;
+        self.scan_progress = Some((0, 0));
+        self.scan_receiver = Some(scan::start_scan(root, Arc::clone(&self.git)));
+    }
+
+    /*
+     * Take over everything the background scan found since the last call.
+     * Called once per frame so the progress bar and the list fill up live.
+     */
+    pub fn poll_scan(&mut self) {
+        let Some(receiver) = &self.scan_receiver else {
+            return;
+        };
+        let mut finished = false;
+        let mut found = Vec::new();
+        while let Ok(message) = receiver.try_recv() {
+            match message {
+                ScanMessage::Progress { done, total } => self.scan_progress = Some((done, total)),
+                ScanMessage::Found(repo) => found.push(repo),
+                ScanMessage::Finished => finished = true,
+            }
+        }
+        for mut repo in found {
+            repo.profile = self
+                .suggest_profile(repo.remote_host.as_deref())
+                .map(|idx| self.entries[idx].alias.clone());
+            self.scan_results.push(repo);
+        }
+        if self.scan_selected.is_none() && !self.scan_results.is_empty() {
+            self.scan_selected = Some(0);
+        }
+        if finished {
+            self.scan_receiver = None;
+            if let Some((_, total)) = self.scan_progress {
+                self.scan_progress = Some((total, total));
+            }
+        }
+    }
+
+    // switch the profile of the highlighted repo to the next one, wrapping to none
+    pub fn cycle_scan_profile(&mut self) {
+        let Some(idx) = self.scan_selected else {
+            return;
+        };
+        let current = self.scan_results[idx]
+            .profile
+            .as_deref()
+            .and_then(|alias| self.entries.iter().position(|entry| entry.alias == alias));
+        let next = match current {
+            None => 0,
+            Some(profile) => profile + 1,
+        };
+        let repo = &mut self.scan_results[idx];
+        repo.profile = self.entries.get(next).map(|entry| entry.alias.clone());
+        repo.selected = repo.profile.is_some();
+    }
+
+    /*
+     * Inject the chosen profile into every selected repo and refresh their identities.
+     */
+    pub fn inject_scanned(&mut self) {
+        let mut injected = 0;
+        for repo_idx in 0..self.scan_results.len() {
+            let repo = &self.scan_results[repo_idx];
+            let (true, Some(alias)) = (repo.selected, repo.profile.clone()) else {
+                continue;
+            };
+            // profiles are looked up by alias, they may have been moved or deleted since
+            let Some(profile) = self.entries.iter().position(|entry| entry.alias == alias) else {
+                continue;
+            };
+            let path = repo.path.clone();
+            if self.inject_profile(profile, InjectScope::Local, &path) {
+                injected += 1;
+            }
+            let mut refreshed = scan::inspect_repo(self.git.as_ref(), &path);
+            refreshed.profile = Some(alias);
+            self.scan_results[repo_idx] = refreshed;
+        }
+        self.status = Some(format!("Injected profiles into {} repos", injected));
+    }
+
+    /*
+     * Look for remotes with tokens in the current repo and every scanned repo.
+     */
+    pub fn open_scrub(&mut self) {
+        let mut repos = vec![self.workdir.clone()];
+        repos.extend(self.scan_results.iter().map(|repo| repo.path.clone()));
+        self.scrub_findings = repos
+            .iter()
+            .filter_map(|repo| scrub::find(self.git.as_ref(), repo, &self.entries).ok())
+            .flatten()
+            .collect();
+        self.current_screen = CurrentScreen::Scrubbing;
+    }
+
+    pub fn scrub_remotes(&mut self) {
+        let exe = if self.scrub_helper {
+            std::env::current_exe().ok()
+        } else {
+            None
+        };
+        let mut failed = 0;
+        for finding in &self.scrub_findings {
+            let helper = self.helper_for(exe.as_deref(), finding);
+            if scrub::scrub(self.git.as_ref(), finding, helper.as_deref()).is_err() {
+                failed += 1;
+            }
+        }
+        if failed > 0 {
+            self.status = Some(format!(
+                "Cleaning {} of {} remotes failed",
+                failed,
+                self.scrub_findings.len()
+            ));
+        }
+        self.scrub_findings.clear();
+    }
+
+    // the credential helper serving the profile owning the token of the finding
+    pub fn helper_for(&self, exe: Option<&Path>, finding: &Finding) -> Option<String> {
+        let alias = finding.profile.as_deref()?;
+        Some(credential::helper_command(
+            exe?,
+            self.store_of(alias),
+            alias,
+            finding.host(),
+        ))
+    }
+
+    /*
+     * Work on another directory from now on. The path is resolved so the title
+     * and the registry show where changes really go.
+     */
+    pub fn set_workdir(&mut self, path: &Path) -> Result<(), String> {
+        let path = input::expand_home(&path.to_string_lossy(), &self.home_dir);
+        if !path.is_dir() {
+            return Err(format!("{} is not a directory", path.display()));
+        }
+        self.workdir =
+            fs::canonicalize(&path).map_err(|err| format!("{}: {}", path.display(), err))?;
+        self.refresh_target();
+        Ok(())
+    }
+
+    // look up the repo and branch of the workdir again, e.g. after a checkout
+    pub fn refresh_target(&mut self) {
+        let git_stdout = |args: &[&str]| {
+            self.git
+                .run(&self.workdir, args)
+                .ok()
+                .filter(|output| output.success)
+                .map(|output| output.stdout.trim().to_string())
+                .filter(|stdout| !stdout.is_empty())
+        };
+        self.target = git_stdout(&["rev-parse", "--show-toplevel"]).map(|root| TargetRepo {
+            root: PathBuf::from(root),
+            branch: git_stdout(&["symbolic-ref", "--short", "-q", "HEAD"]),
+        });
+        self.coauthors = pair::load_session(self.git.as_ref(), &self.workdir);
+    }
+
+    // where injections go, for the title and the confirmation popups
+    pub fn target_label(&self) -> String {
+        match &self.target {
+            Some(TargetRepo {
+                root,
+                branch: Some(branch),
+            }) => format!("{} ({})", root.display(), branch),
+            Some(TargetRepo { root, branch: None }) => {
+                format!("{} (detached HEAD)", root.display())
+            }
+            None => format!("{} (not a git repository)", self.workdir.display()),
+        }
+    }
+
+    pub fn open_repo_picker(&mut self) {
+        let mut current = self.workdir.to_string_lossy().to_string();
+        if !current.ends_with('/') {
+            current.push('/');
+        }
+        self.repo_path_input.set(&current);
+        self.repo_completions.clear();
+        self.current_screen = CurrentScreen::ChoosingRepo;
+    }
+
+    pub fn complete_repo_path(&mut self) {
+        let (completed, matches) =
+            input::complete_path(self.repo_path_input.value(), &self.home_dir);
+        self.repo_path_input.set(&completed);
+        self.repo_completions = matches;
+    }
+
+    pub fn choose_repo(&mut self) {
+        let path = PathBuf::from(self.repo_path_input.value().trim());
+        match self.set_workdir(&path) {
+            Ok(()) => {
+                self.repo_completions.clear();
+                self.status = None;
+                self.current_screen = CurrentScreen::Main;
+            }
+            Err(err) => self.status = Some(err),
+        }
+    }
+
+    /*
+     * Open the Pairing popup with the co-authors of the running session ticked.
+     */
+    pub fn open_pairing(&mut self) {
+        if self.target.is_none() {
+            self.status = Some(format!(
+                "{} is not a git repository",
+                self.workdir.display()
+            ));
+            return;
+        }
+        self.coauthors = pair::load_session(self.git.as_ref(), &self.workdir);
+        self.coauthor_choice = self
+            .entries
+            .iter()
+            .filter(|entry| self.coauthors.contains(&pair::trailer(entry)))
+            .map(|entry| entry.alias.clone())
+            .collect();
+        self.pairing_selected = if self.entries.is_empty() {
+            None
+        } else {
+            Some(0)
+        };
+        self.current_screen = CurrentScreen::Pairing;
+    }
+
+    pub fn toggle_coauthor(&mut self) {
+        let Some(idx) = self.pairing_selected else {
+            return;
+        };
+        let alias = &self.entries[idx].alias;
+        match self
+            .coauthor_choice
+            .iter()
+            .position(|chosen| chosen == alias)
+        {
+            Some(pos) => {
+                self.coauthor_choice.remove(pos);
+            }
+            None => self.coauthor_choice.push(alias.clone()),
+        }
+    }
+
+    /*
+     * Start the session with the ticked profiles, or end it if none is ticked.
+     */
+    pub fn start_pairing(&mut self) {
+        let chosen: Vec<&Entry> = self
+            .entries
+            .iter()
+            .filter(|entry| self.coauthor_choice.contains(&entry.alias))
+            .collect();
+        let result = if chosen.is_empty() {
+            pair::end(self.git.as_ref(), &self.workdir)
         } else {
-            Command::new("sh")
-                .args(["-c", &command])
-                .current_dir(self.workdir.clone())
-                .output()
-                .expect("failed to execute process")
+            pair::start(self.git.as_ref(), &self.workdir, &chosen)
+        };
+        match result {
+            Ok(()) => {
+                self.status = None;
+                self.current_screen = CurrentScreen::Main;
+            }
+            Err(err) => self.status = Some(err),
+        }
+        self.coauthors = pair::load_session(self.git.as_ref(), &self.workdir);
+    }
+
+    pub fn end_pairing(&mut self) {
+        self.coauthor_choice.clear();
+        self.start_pairing();
+    }
+
+    pub fn open_import(&mut self) {
+        if self.import_root_input.value().is_empty() {
+            self.import_root_input
+                .insert_str(&self.home_dir.join("src").to_string_lossy());
+        }
+        self.import_candidates = None;
+        self.current_screen = CurrentScreen::Importing;
+    }
+
+    /*
+     * Collect the import candidates. Repos are only searched for when a
+     * directory is given, the global configuration is always read.
+     */
+    pub fn discover_imports(&mut self) {
+        let root = PathBuf::from(self.import_root_input.value().trim());
+        let repo_root = if root.as_os_str().is_empty() {
+            None
+        } else if root.is_dir() {
+            Some(root.as_path())
+        } else {
+            self.status = Some(format!("{} is not a directory", root.display()));
+            return;
+        };
+        let candidates =
+            discover::discover(self.git.clone(), &self.home_dir, repo_root, &self.entries);
+        self.import_selected = if candidates.is_empty() { None } else { Some(0) };
+        self.import_candidates = Some(candidates);
+    }
+
+    pub fn import_chosen(&mut self) {
+        let Some(candidates) = self.import_candidates.take() else {
+            return;
         };
+        let imported: Vec<Entry> = candidates
+            .into_iter()
+            .filter(|candidate| candidate.selected)
+            .map(|candidate| candidate.entry)
+            .collect();
+        self.current_screen = CurrentScreen::Main;
+        if imported.is_empty() {
+            return;
+        }
+        let count = imported.len();
+        for entry in &imported {
+            self.audit(Action::Create, &entry.alias, None, "import");
+        }
+        self.with_own_entries(|own| own.extend(imported));
+        self.mark_dirty();
+        if !self.dirty {
+            self.status = Some(format!("Imported {} profiles", count));
+        }
+    }
+
+    pub fn import_bundle(&mut self, incoming: Vec<Incoming>) -> MergeSummary {
+        let before = self.own_entries();
+        let summary = self.with_own_entries(|own| bundle::merge(own, incoming));
+        for entry in self.own_entries() {
+            let previous = before.iter().find(|previous| previous.alias == entry.alias);
+            match previous {
+                None => self.audit(Action::Create, &entry.alias, None, "bundle"),
+                Some(previous) if previous.to_string() != entry.to_string() => {
+                    self.audit(Action::Edit, &entry.alias, None, "replaced from bundle")
+                }
+                Some(_) => {}
+            }
+        }
+        if summary.added + summary.overwritten + summary.renamed > 0 {
+            self.mark_dirty();
+        }
+        summary
+    }
+
+    pub fn open_bundle(&mut self) {
+        self.bundle_incoming = None;
+        self.bundle_asks_passphrase = false;
+        self.bundle_passphrase_input.clear();
+        self.current_screen = CurrentScreen::Bundle;
     }
 
-    pub fn inject_selected_profile(&self) {
+    /*
+     * Read the bundle at the entered path. Encrypted bundles first ask for the
+     * passphrase, then the profiles are compared to the local ones.
+     */
+    pub fn load_bundle(&mut self) {
+        let path = PathBuf::from(self.bundle_path_input.value().trim());
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(err) => {
+                self.status = Some(format!("{}: {}", path.display(), err));
+                return;
+            }
+        };
+        if bundle::is_encrypted(&content) && !self.bundle_asks_passphrase {
+            self.bundle_asks_passphrase = true;
+            return;
+        }
+        let passphrase = self.bundle_passphrase_input.value().to_string();
+        let passphrase = self.bundle_asks_passphrase.then_some(passphrase.as_str());
+        match bundle::read(&content, passphrase) {
+            Ok(entries) => {
+                let incoming = bundle::plan_merge(&self.own_entries(), entries, None);
+                self.bundle_selected = if incoming.is_empty() { None } else { Some(0) };
+                self.bundle_incoming = Some(incoming);
+                self.status = None;
+            }
+            Err(err) => {
+                self.bundle_passphrase_input.clear();
+                self.status = Some(err);
+            }
+        }
+    }
+
+    // cycle what happens to the selected profile if it conflicts with a local one
+    pub fn cycle_bundle_resolution(&mut self) {
+        let (Some(incoming), Some(idx)) = (self.bundle_incoming.as_mut(), self.bundle_selected)
+        else {
+            return;
+        };
+        if incoming[idx].conflict {
+            incoming[idx].resolution = incoming[idx].resolution.next();
+        }
+    }
+
+    pub fn merge_bundle(&mut self) {
+        let Some(incoming) = self.bundle_incoming.take() else {
+            return;
+        };
+        self.current_screen = CurrentScreen::Main;
+        let summary = self.import_bundle(incoming);
+        if !self.dirty {
+            self.status = Some(format!("Bundle imported: {}", summary));
+        }
+    }
+
+    pub fn open_rotation(&mut self) {
+        if self.selected_index.is_none() || !self.selected_is_writable() {
+            return;
+        }
+        self.new_token_input.clear();
+        self.rotation_summary.clear();
+        self.current_screen = CurrentScreen::Rotating;
+    }
+
+    pub fn rotate_selected_token(&mut self) {
         let Some(idx) = self.selected_index else {
             return;
         };
-        let selected_entry = &self.entries[idx];
-        let author_name = if selected_entry.nickname.len() > 0 {
-            selected_entry.nickname.clone()
+        let new_token = self.new_token_input.value().trim().to_string();
+        self.new_token_input.clear();
+        self.rotation_summary = match self.rotate_token(idx, &new_token, self.rotate_validate) {
+            Ok(summary) => summary,
+            Err(err) => vec![err, "Nothing was changed".to_string()],
+        };
+    }
+
+    /*
+     * Replace the token of a profile in the store and wherever the old one is still
+     * in use: remotes of the current, registered and scanned repos and
+     * ~/.git-credentials. Returns the summary of what was updated.
+     * The store is saved first, the repos are left alone if that fails so they
+     * never use a token the store does not have.
+     */
+    pub fn rotate_token(
+        &mut self,
+        idx: usize,
+        new_token: &str,
+        validate: bool,
+    ) -> Result<Vec<String>, String> {
+        if new_token.is_empty() {
+            return Err("The new token is empty".to_string());
+        }
+        let entry = self.entries[idx].clone();
+        if let Some(path) = &entry.shared_from {
+            return Err(format!(
+                "{} comes from the shared store {} and is read-only",
+                entry.alias,
+                path.display()
+            ));
+        }
+        if new_token == entry.pa_token {
+            return Err("The new token is the current one".to_string());
+        }
+        if validate {
+            self.validator
+                .validate(&entry, new_token)
+                .map_err(|err| format!("Validation failed: {}", err))?;
+        }
+        let was_dirty = self.dirty;
+        self.entries[idx].pa_token = new_token.to_string();
+        self.mark_dirty();
+        if self.dirty {
+            self.entries[idx].pa_token = entry.pa_token.clone();
+            self.dirty = was_dirty;
+            return Err(match &self.status {
+                Some(status) => status.clone(),
+                None => "The store changed on disk since it was loaded".to_string(),
+            });
+        }
+        self.audit(Action::Rotate, &entry.alias, None, "");
+        let repos = self.known_repos();
+        let report = rotate::rotate(
+            self.git.as_ref(),
+            &entry,
+            new_token,
+            &repos,
+            &self.home_dir.join(".git-credentials"),
+        );
+        Ok(report.summary(&entry.alias))
+    }
+
+    // the workdir, registered and scanned repos, each once
+    fn known_repos(&self) -> Vec<PathBuf> {
+        let mut repos: Vec<PathBuf> = std::iter::once(&self.workdir)
+            .chain(self.repos.iter().map(|record| &record.path))
+            .chain(self.scan_results.iter().map(|repo| &repo.path))
+            .map(|path| fs::canonicalize(path).unwrap_or_else(|_| path.clone()))
+            .collect();
+        repos.sort();
+        repos.dedup();
+        repos
+    }
+
+    // append to the audit log, a failure only ends up in the status
+    pub fn audit(&mut self, action: Action, alias: &str, repo: Option<&Path>, detail: &str) {
+        let event = AuditEvent::new(action, alias, repo, detail);
+        if let Err(err) = audit::append(&self.audit_file, &event) {
+            self.status = Some(format!("Writing the audit log failed: {}", err));
+        }
+    }
+
+    pub fn open_audit_log(&mut self) {
+        self.audit_events = audit::load(&self.audit_file);
+        self.audit_filter_input.clear();
+        self.refilter_audit();
+        self.current_screen = CurrentScreen::AuditLog;
+    }
+
+    // the events matching the filter, newest first
+    pub fn filtered_audit(&self) -> Vec<&AuditEvent> {
+        let filter = self.audit_filter_input.value().to_lowercase();
+        self.audit_events
+            .iter()
+            .rev()
+            .filter(|event| event.describe().to_lowercase().contains(&filter))
+            .collect()
+    }
+
+    pub fn refilter_audit(&mut self) {
+        self.audit_selected = if self.filtered_audit().is_empty() {
+            None
         } else {
-            selected_entry.username.clone()
+            Some(0)
         };
-        let inject_username = format!("git config --local user.name '{}'", author_name);
-        let inject_email = format!("git config --local user.email '{}'", selected_entry.email);
-        self.exec_cmd(inject_username);
-        self.exec_cmd(inject_email);
     }
 
-    pub fn save_all_data(&self) {
-        let mut content = String::new();
-        for entry in &self.entries {
-            content.push_str(format!("{}\n", entry.to_string()).as_str());
+    // record a mutation and write it to the store right away
+    fn mark_dirty(&mut self) {
+        self.dirty = true;
+        self.save_all_data();
+    }
+
+    /*
+     * Changes that would be lost when quitting now: mutations that could not be
+     * saved and a profile that is still being typed into the editor.
+     */
+    pub fn has_unsaved_changes(&self) -> bool {
+        let editing_unsaved = self.current_screen == CurrentScreen::Editing
+            && [
+                &self.alias_input,
+                &self.username_input,
+                &self.email_input,
+                &self.token_input,
+                &self.nickname_input,
+                &self.host_input,
+                &self.provider_input,
+            ]
+            .iter()
+            .any(|input| !input.value().is_empty());
+        self.dirty || editing_unsaved
+    }
+
+    /*
+     * Quit if nothing would be lost, otherwise ask the user what to do first.
+     */
+    pub fn request_quit(&mut self) {
+        if self.dirty {
+            self.save_all_data();
+        }
+        if self.current_screen == CurrentScreen::Conflict {
+            return;
+        }
+        if self.has_unsaved_changes() {
+            if self.current_screen != CurrentScreen::Quitting {
+                self.quit_return_screen = self.current_screen;
+            }
+            self.current_screen = CurrentScreen::Quitting;
+        } else {
+            self.closing = true;
+        }
+    }
+
+    /*
+     * Store a profile left in the editor, write everything and quit unless saving failed.
+     */
+    pub fn save_and_quit(&mut self) {
+        self.current_screen = self.quit_return_screen;
+        if self.current_screen == CurrentScreen::Editing {
+            self.current_screen = CurrentScreen::Main;
+            self.store_entries();
+            self.clear();
+        } else {
+            self.save_all_data();
+        }
+        if !self.dirty && self.current_screen != CurrentScreen::Conflict {
+            self.closing = true;
+        }
+    }
+
+    /*
+     * Persist the profiles and the trash. Called after every mutation.
+     * If the store changed on disk since it was loaded nothing is written and the
+     * conflict screen is opened instead.
+     */
+    pub fn save_all_data(&mut self) {
+        if let Err(err) = self.try_save() {
+            self.status = Some(format!("Saving failed: {}", err));
+        }
+    }
+
+    fn try_save(&mut self) -> io::Result<()> {
+        let _lock = StoreLock::acquire(&self.save_file)?;
+        let on_disk = fs::read_to_string(&self.save_file).unwrap_or_default();
+        if on_disk != self.loaded_content {
+            self.in_conflict = true;
+            self.current_screen = CurrentScreen::Conflict;
+            return Ok(());
+        }
+        self.write_store()
+    }
+
+    // must only be called while holding the store lock
+    fn write_store(&mut self) -> io::Result<()> {
+        let content = store::serialize_entries(&self.own_entries());
+        if content != self.loaded_content {
+            if !store::differs_only_in_usage(&content, &self.loaded_content) {
+                store::rotate_backups(&self.save_file)?;
+            }
+            store::write_atomic(&self.save_file, &content)?;
+            self.loaded_content = content;
+        }
+        trash::save_trash(&self.trash_file, &self.trash)?;
+        self.status = None;
+        self.dirty = false;
+        Ok(())
+    }
+
+    /*
+     * Resolve a conflict by merging the profiles on disk with the ones in memory.
+     */
+    pub fn merge_external_changes(&mut self) {
+        let result = StoreLock::acquire(&self.save_file).and_then(|_lock| {
+            let on_disk = fs::read_to_string(&self.save_file).unwrap_or_default();
+            let base = store::parse_entries(&self.loaded_content);
+            let disk = store::parse_entries(&on_disk);
+            self.with_own_entries(|own| *own = store::merge_entries(&base, &disk, own));
+            self.loaded_content = on_disk;
+            self.write_store()
+        });
+        if let Err(err) = result {
+            self.status = Some(format!("Merging failed: {}", err));
+        }
+        self.in_conflict = false;
+        self.clamp_selection();
+        self.current_screen = CurrentScreen::Main;
+    }
+
+    /*
+     * Resolve a conflict by dropping the in memory profiles in favour of the ones on disk.
+     */
+    pub fn reload_store(&mut self) {
+        let result = StoreLock::acquire(&self.save_file).map(|_lock| {
+            let on_disk = fs::read_to_string(&self.save_file).unwrap_or_default();
+            self.with_own_entries(|own| *own = store::parse_entries(&on_disk));
+            self.loaded_content = on_disk;
+            self.dirty = false;
+        });
+        if let Err(err) = result {
+            self.status = Some(format!("Reloading failed: {}", err));
+        }
+        self.in_conflict = false;
+        self.clamp_selection();
+        self.current_screen = CurrentScreen::Main;
+    }
+
+    fn clamp_selection(&mut self) {
+        if let Some(idx) = self.selected_index {
+            if idx >= self.entries.len() {
+                self.selected_index = self.entries.len().checked_sub(1);
+            }
         }
-        fs::write(&self.save_file, content).expect("unable to write entry to file");
     }
 
     pub fn clone_repo(&mut self) {
-        let re = Regex::new("^https://(?<url>.+)$").unwrap();
-        let Some(re_match) = re.captures(&self.clone_url_input) else {
-            panic!("Illegal url input")
-        };
-        let url = &re_match["url"];
-        let clone_command = match self.selected_index {
-            Some(idx) => format!(
-                "git clone https://{}:{}@{}",
-                self.entries[idx].username, self.entries[idx].pa_token, url
-            ),
-            None => panic!("No profile selected"),
-        };
-        let re_path = Regex::new("(?<path>[^/]*).git").unwrap();
-        let Some(path_match) = re_path.captures(url) else {
-            panic!("Illegal url input")
-        };
-        let clone_path = &path_match["path"];
-        let workdir_backup = self.workdir.clone();
-        self.exec_cmd(clone_command);
-        self.workdir = clone_path.to_string();
-        self.inject_selected_profile();
-        self.workdir = workdir_backup;
+        let Some(idx) = self.selected_index else {
+            self.status = Some("No profile selected".to_string());
+            return;
+        };
+        let repo_url = match provider::parse_clone_url(self.clone_url_input.value()) {
+            Ok(repo_url) => repo_url,
+            Err(err) => {
+                self.status = Some(err);
+                return;
+            }
+        };
+        let Ok(exe) = std::env::current_exe() else {
+            self.status = Some("Unable to find the gat executable for git to ask".to_string());
+            return;
+        };
+        let alias = self.entries[idx].alias.clone();
+        // the token comes from the helper, so it never ends up in the remote url
+        let helper =
+            credential::helper_command(&exe, self.store_of(&alias), &alias, &repo_url.host);
+        let host_url = format!("https://{}", repo_url.host);
+        let reset = format!("credential.{}.helper=", host_url);
+        let use_helper = format!("{}{}", reset, helper);
+        let workdir = self.workdir.clone();
+        let args = ["-c", &reset, "-c", &use_helper, "clone", &repo_url.https()];
+        if self.run_git(&workdir, &args).is_none() {
+            return;
+        }
+        let clone_path = workdir.join(&repo_url.name);
+        if let Err(err) =
+            scrub::set_helper(self.git.as_ref(), &clone_path, &host_url, &helper, None)
+        {
+            self.status = Some(err);
+        }
+        self.audit(Action::Clone, &alias, Some(&clone_path), &repo_url.https());
+        // a fresh clone always gets the profile in its own config
+        self.inject_profile(idx, InjectScope::Local, &clone_path);
     }
 }
@@ -0,0 +1,181 @@
+use crate::store;
+use crate::trash;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Action {
+    Inject,
+    Remove, // the keys gat wrote for the profile were removed from a repo
+    Clone,
+    Exec,   // a command ran as the profile through gat exec
+    Reveal, // the token left gat, e.g. to the credential helper or an export
+    Create,
+    Edit,
+    Delete,
+    Restore, // a deleted profile came back from the trash or through undo
+    Rotate,
+}
+
+impl Action {
+    const ALL: [Action; 10] = [
+        Action::Inject,
+        Action::Remove,
+        Action::Clone,
+        Action::Exec,
+        Action::Reveal,
+        Action::Create,
+        Action::Edit,
+        Action::Delete,
+        Action::Restore,
+        Action::Rotate,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Action::Inject => "inject",
+            Action::Remove => "remove",
+            Action::Clone => "clone",
+            Action::Exec => "exec",
+            Action::Reveal => "reveal",
+            Action::Create => "create",
+            Action::Edit => "edit",
+            Action::Delete => "delete",
+            Action::Restore => "restore",
+            Action::Rotate => "rotate",
+        }
+    }
+
+    pub fn from_label(label: &str) -> Option<Action> {
+        Action::ALL
+            .into_iter()
+            .find(|action| action.label() == label)
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct AuditEvent {
+    pub time: u64, // unix timestamp in seconds
+    pub action: Action,
+    pub alias: String,
+    pub repo: Option<PathBuf>,
+    pub detail: String, // e.g. the scope of an injection or who a token was revealed to
+}
+
+impl AuditEvent {
+    pub fn new(action: Action, alias: &str, repo: Option<&Path>, detail: &str) -> AuditEvent {
+        AuditEvent {
+            time: trash::now(),
+            action,
+            alias: alias.to_string(),
+            repo: repo.map(Path::to_path_buf),
+            detail: detail.to_string(),
+        }
+    }
+
+    // a line for people, also what the log screen filters on
+    pub fn describe(&self) -> String {
+        let repo = self
+            .repo
+            .as_ref()
+            .map(|repo| repo.display().to_string())
+            .unwrap_or_default();
+        format!(
+            "{}  {:<7} {:<12} {} {}",
+            format_time(self.time),
+            self.action.label(),
+            self.alias,
+            repo,
+            self.detail
+        )
+        .trim_end()
+        .to_string()
+    }
+
+    fn to_line(&self) -> String {
+        let repo = self
+            .repo
+            .as_ref()
+            .map(|repo| repo.to_string_lossy().to_string())
+            .unwrap_or_default();
+        format!(
+            "{},{},{},{},{}\n",
+            self.time,
+            self.action.label(),
+            store::escape_field(&self.alias),
+            store::escape_field(&repo),
+            store::escape_field(&self.detail)
+        )
+    }
+
+    fn from_line(line: &str) -> Option<AuditEvent> {
+        let fields: Vec<&str> = line.split(',').collect();
+        let [time, action, alias, repo, detail] = fields.as_slice() else {
+            return None;
+        };
+        let repo = store::unescape_field(repo);
+        Some(AuditEvent {
+            time: time.parse().ok()?,
+            action: Action::from_label(action)?,
+            alias: store::unescape_field(alias),
+            repo: (!repo.is_empty()).then(|| PathBuf::from(repo)),
+            detail: store::unescape_field(detail),
+        })
+    }
+}
+
+/*
+* The audit log lives next to the profile store, i.e. ~/.gat.log for ~/.gat.
+*/
+pub fn audit_file(save_file: &Path) -> PathBuf {
+    let mut name = save_file.as_os_str().to_owned();
+    name.push(".log");
+    PathBuf::from(name)
+}
+
+/*
+* Events are only ever appended, one line each, so concurrent gat processes
+* like the credential helper never lose each other's events.
+*/
+pub fn append(audit_file: &Path, event: &AuditEvent) -> io::Result<()> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(audit_file)?;
+    file.write_all(event.to_line().as_bytes())
+}
+
+// the events in the order they happened, unreadable lines are skipped
+pub fn load(audit_file: &Path) -> Vec<AuditEvent> {
+    fs::read_to_string(audit_file)
+        .unwrap_or_default()
+        .lines()
+        .filter_map(AuditEvent::from_line)
+        .collect()
+}
+
+// a unix timestamp as UTC date and time, e.g. 2024-03-01 09:30:00
+pub fn format_time(time: u64) -> String {
+    let days = (time / 86_400) as i64;
+    let secs = time % 86_400;
+    // civil date from days since 1970-01-01, after Howard Hinnant's algorithm
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        year,
+        month,
+        day,
+        secs / 3_600,
+        secs % 3_600 / 60,
+        secs % 60
+    )
+}
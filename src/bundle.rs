@@ -0,0 +1,265 @@
+use crate::app::Entry;
+use crate::discover::unique_alias;
+use crate::store;
+use argon2::Argon2;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use std::io::{self, Write};
+use std::path::Path;
+
+/*
+* A bundle is a header followed by profiles in the store format:
+*
+*   gat-bundle 1
+*   tokens encrypted
+*   salt <base64>
+*   work,jdoe,j@doe.dev,<base64 nonce and ciphertext>,,host=github.com
+*
+* With tokens excluded the token column is empty, in plain bundles it is the token.
+*/
+const HEADER: &str = "gat-bundle 1";
+const NONCE_LEN: usize = 12;
+const SALT_LEN: usize = 16;
+
+pub enum TokenMode {
+    Plain,
+    Excluded,
+    Encrypted(String), // passphrase the key is derived from
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<Key, String> {
+    let mut key = Key::default();
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|err| format!("Unable to derive the key: {}", err))?;
+    Ok(key)
+}
+
+fn random_bytes(len: usize) -> Result<Vec<u8>, String> {
+    let mut bytes = vec![0; len];
+    getrandom::getrandom(&mut bytes).map_err(|err| format!("No randomness available: {}", err))?;
+    Ok(bytes)
+}
+
+pub fn export(entries: &[Entry], tokens: &TokenMode) -> Result<String, String> {
+    let mut content = format!("{}\n", HEADER);
+    let cipher = match tokens {
+        TokenMode::Plain => {
+            content.push_str("tokens plain\n");
+            None
+        }
+        TokenMode::Excluded => {
+            content.push_str("tokens excluded\n");
+            None
+        }
+        TokenMode::Encrypted(passphrase) => {
+            let salt = random_bytes(SALT_LEN)?;
+            content.push_str("tokens encrypted\n");
+            content.push_str(&format!("salt {}\n", URL_SAFE_NO_PAD.encode(&salt)));
+            Some(ChaCha20Poly1305::new(&derive_key(passphrase, &salt)?))
+        }
+    };
+    let mut exported = Vec::new();
+    for entry in entries {
+        let mut entry = entry.clone();
+        entry.pa_token = match (tokens, &cipher) {
+            (TokenMode::Excluded, _) => String::new(),
+            (_, Some(cipher)) if !entry.pa_token.is_empty() => {
+                let nonce = random_bytes(NONCE_LEN)?;
+                let mut sealed = cipher
+                    .encrypt(Nonce::from_slice(&nonce), entry.pa_token.as_bytes())
+                    .map_err(|_| "Encrypting a token failed".to_string())?;
+                let mut bytes = nonce;
+                bytes.append(&mut sealed);
+                URL_SAFE_NO_PAD.encode(bytes)
+            }
+            _ => entry.pa_token,
+        };
+        exported.push(entry);
+    }
+    content.push_str(&store::serialize_entries(&exported));
+    Ok(content)
+}
+
+/*
+* Write a bundle readable by its owner only, like the store. Plain bundles hold
+* the tokens in the clear.
+*/
+pub fn write_file(path: &Path, content: &str) -> io::Result<()> {
+    store::create_private(path)?.write_all(content.as_bytes())
+}
+
+// whether reading the bundle needs the passphrase it was exported with
+pub fn is_encrypted(content: &str) -> bool {
+    content.lines().nth(1) == Some("tokens encrypted")
+}
+
+/*
+* Read the profiles of a bundle. Tokens of encrypted bundles are decrypted with
+* the passphrase, a wrong passphrase fails the whole import.
+*/
+pub fn read(content: &str, passphrase: Option<&str>) -> Result<Vec<Entry>, String> {
+    let mut lines = content.lines();
+    if lines.next() != Some(HEADER) {
+        return Err("This is not a gat bundle".to_string());
+    }
+    let cipher = match lines.next() {
+        Some("tokens plain") | Some("tokens excluded") => None,
+        Some("tokens encrypted") => {
+            let salt = lines
+                .next()
+                .and_then(|line| line.strip_prefix("salt "))
+                .and_then(|salt| URL_SAFE_NO_PAD.decode(salt).ok())
+                .ok_or("The bundle has no valid salt")?;
+            let passphrase = passphrase.ok_or("The bundle needs a passphrase")?;
+            Some(ChaCha20Poly1305::new(&derive_key(passphrase, &salt)?))
+        }
+        _ => return Err("The bundle uses an unknown token mode".to_string()),
+    };
+    let rest: Vec<&str> = lines.collect();
+    let mut entries = store::parse_entries(&rest.join("\n"));
+    if let Some(cipher) = cipher {
+        for entry in entries
+            .iter_mut()
+            .filter(|entry| !entry.pa_token.is_empty())
+        {
+            let bytes = URL_SAFE_NO_PAD
+                .decode(&entry.pa_token)
+                .ok()
+                .filter(|bytes| bytes.len() > NONCE_LEN)
+                .ok_or("The bundle contains a damaged token")?;
+            let (nonce, sealed) = bytes.split_at(NONCE_LEN);
+            let token = cipher
+                .decrypt(Nonce::from_slice(nonce), sealed)
+                .map_err(|_| "Wrong passphrase".to_string())?;
+            entry.pa_token = String::from_utf8(token).map_err(|err| err.to_string())?;
+        }
+    }
+    Ok(entries)
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Resolution {
+    Skip,      // keep the local profile
+    Overwrite, // replace the local profile, keeping its token if the bundle has none
+    Rename,    // import under a new alias
+}
+
+impl Resolution {
+    pub fn next(self) -> Resolution {
+        match self {
+            Resolution::Skip => Resolution::Overwrite,
+            Resolution::Overwrite => Resolution::Rename,
+            Resolution::Rename => Resolution::Skip,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Resolution::Skip => "skip",
+            Resolution::Overwrite => "overwrite",
+            Resolution::Rename => "rename",
+        }
+    }
+
+    pub fn from_label(label: &str) -> Option<Resolution> {
+        [Resolution::Skip, Resolution::Overwrite, Resolution::Rename]
+            .into_iter()
+            .find(|resolution| resolution.label() == label)
+    }
+}
+
+// a profile from a bundle and what importing it will do
+pub struct Incoming {
+    pub entry: Entry,
+    pub conflict: bool, // a local profile has the same alias but different content
+    pub resolution: Resolution,
+}
+
+/*
+* Compare the bundle to the local profiles. Identical profiles are skipped,
+* conflicting ones get the given resolution, if any.
+*/
+pub fn plan_merge(
+    existing: &[Entry],
+    bundle: Vec<Entry>,
+    on_conflict: Option<Resolution>,
+) -> Vec<Incoming> {
+    bundle
+        .into_iter()
+        .map(|entry| {
+            let local = existing.iter().find(|local| local.alias == entry.alias);
+            // when a profile was last used is local bookkeeping, not a conflict
+            let identical = local.is_some_and(|local| {
+                let mut compared = entry.clone();
+                if compared.pa_token.is_empty() {
+                    compared.pa_token = local.pa_token.clone();
+                }
+                store::differs_only_in_usage(&compared.to_string(), &local.to_string())
+            });
+            let (conflict, resolution) = match local {
+                None => (false, Resolution::Overwrite),
+                Some(_) if identical => (false, Resolution::Skip),
+                Some(_) => (true, on_conflict.unwrap_or(Resolution::Skip)),
+            };
+            Incoming {
+                entry,
+                conflict,
+                resolution,
+            }
+        })
+        .collect()
+}
+
+#[derive(Default, Debug, PartialEq)]
+pub struct MergeSummary {
+    pub added: usize,
+    pub overwritten: usize,
+    pub renamed: usize,
+    pub skipped: usize,
+}
+
+impl std::fmt::Display for MergeSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{} added, {} overwritten, {} renamed, {} skipped",
+            self.added, self.overwritten, self.renamed, self.skipped
+        )
+    }
+}
+
+pub fn merge(entries: &mut Vec<Entry>, incoming: Vec<Incoming>) -> MergeSummary {
+    let mut summary = MergeSummary::default();
+    for Incoming {
+        mut entry,
+        resolution,
+        ..
+    } in incoming
+    {
+        let local = entries.iter().position(|local| local.alias == entry.alias);
+        match (local, resolution) {
+            (None, _) => {
+                entries.push(entry);
+                summary.added += 1;
+            }
+            (Some(_), Resolution::Skip) => summary.skipped += 1,
+            (Some(idx), Resolution::Overwrite) => {
+                if entry.pa_token.is_empty() {
+                    entry.pa_token = entries[idx].pa_token.clone();
+                }
+                entries[idx] = entry;
+                summary.overwritten += 1;
+            }
+            (Some(_), Resolution::Rename) => {
+                let taken: Vec<String> = entries.iter().map(|entry| entry.alias.clone()).collect();
+                entry.alias = unique_alias(&entry.alias, &taken);
+                entries.push(entry);
+                summary.renamed += 1;
+            }
+        }
+    }
+    summary
+}
@@ -0,0 +1,626 @@
+use crate::app::{App, EntryJson, ProfileJson, ProfilesJson, JSON_VERSION};
+use crate::audit::Action;
+use crate::bundle::{self, Resolution, TokenMode};
+use crate::credential;
+use crate::exec;
+use crate::inject::InjectScope;
+use crate::scan;
+use crate::scrub::{self, Finding};
+use crate::shell::{self, Binding, StatusJson};
+use clap::{Parser, Subcommand};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+#[derive(Parser)]
+#[command(name = "gat", version, about = "Manage git profiles and access tokens")]
+pub struct Cli {
+    /// Profile store to use instead of GAT_STORE, ~/.config/gat/store or ~/.gat
+    #[arg(long, global = true, value_name = "FILE")]
+    pub store: Option<PathBuf>,
+    /// Read-only store whose profiles are listed too, e.g. one shared by a team
+    #[arg(long, global = true, value_name = "FILE")]
+    pub shared: Vec<PathBuf>,
+    /// Work on the repository at PATH instead of the current directory
+    #[arg(short = 'C', global = true, value_name = "PATH")]
+    pub directory: Option<PathBuf>,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Remove tokens embedded in remote urls, e.g. by clones made with gat
+    Scrub {
+        /// Repository to clean, the current directory by default
+        path: Option<PathBuf>,
+        /// Clean every repository below the path instead
+        #[arg(short, long)]
+        recursive: bool,
+        /// Only report the remotes that would be rewritten
+        #[arg(long)]
+        dry_run: bool,
+        /// Let git ask gat for the token of the owning profile from now on
+        #[arg(long)]
+        helper: bool,
+    },
+    /// Replace the token of a profile everywhere it is used, reading the new one from stdin
+    Rotate {
+        alias: String,
+        /// Use the token without checking it with the forge API first
+        #[arg(long)]
+        no_validate: bool,
+    },
+    /// Write profiles to a bundle that `gat import` reads on another machine
+    Export {
+        /// Bundle file to write, stdout by default
+        file: Option<PathBuf>,
+        /// Profiles to export, all by default
+        #[arg(short, long = "alias")]
+        aliases: Vec<String>,
+        /// How tokens are written, encrypted ones need a passphrase from GAT_PASSPHRASE or stdin
+        #[arg(long, default_value = "exclude", value_parser = ["exclude", "plain", "encrypt"])]
+        tokens: String,
+    },
+    /// Merge the profiles of a bundle into the store
+    Import {
+        file: PathBuf,
+        /// What to do with profiles whose alias exists with different content
+        #[arg(long, value_parser = ["skip", "overwrite", "rename"])]
+        on_conflict: Option<String>,
+    },
+    /// Print the hook switching the identity on cd, for eval in the shell's rc file
+    ShellInit {
+        #[arg(value_parser = ["bash", "zsh", "fish"])]
+        shell: String,
+    },
+    /// List the profiles
+    List {
+        /// Print JSON for scripts
+        #[arg(long)]
+        json: bool,
+        /// Include the tokens instead of redacting them
+        #[arg(long)]
+        show_secrets: bool,
+    },
+    /// Show a single profile
+    Show {
+        alias: String,
+        /// Print JSON for scripts
+        #[arg(long)]
+        json: bool,
+        /// Include the token instead of redacting it
+        #[arg(long)]
+        show_secrets: bool,
+    },
+    /// Show the identity of the repo and the profile bound to it
+    Status {
+        /// Print JSON for scripts, after applying the bound profile with --apply
+        #[arg(long)]
+        json: bool,
+        /// Inject the bound profile if another identity is in use
+        #[arg(long)]
+        apply: bool,
+        /// Only print something if the identity is wrong or was switched
+        #[arg(short, long)]
+        quiet: bool,
+    },
+    /// Print the alias of the active profile for the shell prompt, with a '!' if it is the wrong one
+    Prompt,
+    /// Run a command as a profile without changing any git config, e.g. gat exec work -- git push
+    Exec {
+        alias: String,
+        #[arg(required = true, trailing_var_arg = true, allow_hyphen_values = true)]
+        command: Vec<String>,
+    },
+    /// Git credential helper serving the token of a profile
+    #[command(hide = true)]
+    Credential {
+        alias: String,
+        operation: String,
+        /// Host the helper serves, the one of the profile if not given
+        #[arg(long)]
+        host: Option<String>,
+    },
+}
+
+/*
+* Run a subcommand against the profiles of the app, the TUI is only started
+* without one.
+*/
+pub fn run(command: Command, app: &mut App) -> ExitCode {
+    let mut stdout = io::stdout();
+    let result = match command {
+        Command::Scrub {
+            path,
+            recursive,
+            dry_run,
+            helper,
+        } => {
+            let path = path.unwrap_or_else(|| app.workdir.clone());
+            scrub_command(app, &path, recursive, dry_run, helper, &mut stdout)
+        }
+        Command::Export {
+            file,
+            aliases,
+            tokens,
+        } => export_command(app, file.as_deref(), &aliases, &tokens),
+        Command::Import { file, on_conflict } => import_command(app, &file, on_conflict.as_deref()),
+        Command::Rotate { alias, no_validate } => rotate_command(app, &alias, !no_validate),
+        Command::ShellInit { shell } => shell::init_script(&shell)
+            .and_then(|script| write!(stdout, "{}", script).map_err(|err| err.to_string())),
+        Command::List { json, show_secrets } => list_command(app, json, show_secrets, &mut stdout),
+        Command::Show {
+            alias,
+            json,
+            show_secrets,
+        } => show_command(app, &alias, json, show_secrets, &mut stdout),
+        Command::Status {
+            json: true, apply, ..
+        } => {
+            // the switch is reported through the JSON, not as a message
+            let applied = if apply {
+                status_command(app, true, true, &mut io::sink())
+            } else {
+                Ok(())
+            };
+            applied.and_then(|()| {
+                let status = shell::repo_status(app);
+                print_json(&StatusJson::new(app, status.as_ref()), &mut stdout)
+            })
+        }
+        Command::Status { apply, quiet, .. } => status_command(app, apply, quiet, &mut stdout),
+        Command::Prompt => {
+            let status = shell::repo_status(app);
+            writeln!(stdout, "{}", shell::prompt(app, status.as_ref()))
+                .map_err(|err| err.to_string())
+        }
+        Command::Exec { alias, command } => match exec_command(app, &alias, &command) {
+            Ok(code) => return code,
+            Err(err) => Err(err),
+        },
+        Command::Credential {
+            alias,
+            operation,
+            host,
+        } => {
+            // the attributes of the request, e.g. protocol and host
+            let mut input = String::new();
+            let _ = io::stdin().read_to_string(&mut input);
+            let entry = app.entries.iter().find(|entry| entry.alias == alias);
+            let answer = credential::respond(entry, &operation, &input, host.as_deref());
+            if !answer.is_empty() {
+                let workdir = app.workdir.clone();
+                app.audit(Action::Reveal, &alias, Some(&workdir), "credential helper");
+            }
+            write!(stdout, "{}", answer).map_err(|err| err.to_string())
+        }
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("gat: {}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+pub fn describe(finding: &Finding) -> String {
+    let owner = match &finding.profile {
+        Some(alias) => format!("token of profile {}", alias),
+        None => "token of no known profile".to_string(),
+    };
+    format!(
+        "{}: {} {} ({})",
+        finding.repo.display(),
+        finding.key,
+        finding.redacted_url(),
+        owner
+    )
+}
+
+/*
+* Report and rewrite remotes with embedded tokens in one repo or a whole tree.
+*/
+pub fn scrub_command(
+    app: &App,
+    path: &Path,
+    recursive: bool,
+    dry_run: bool,
+    helper: bool,
+    out: &mut dyn Write,
+) -> Result<(), String> {
+    let repos = if recursive {
+        scan::scan_all(path, app.git.clone())
+            .into_iter()
+            .map(|repo| repo.path)
+            .collect()
+    } else {
+        vec![path.to_path_buf()]
+    };
+    let exe = if helper {
+        Some(std::env::current_exe().map_err(|err| err.to_string())?)
+    } else {
+        None
+    };
+    let mut findings = Vec::new();
+    for repo in &repos {
+        match scrub::find(app.git.as_ref(), repo, &app.entries) {
+            Ok(found) => findings.extend(found),
+            // a tree may contain broken repos, only a single repo is worth failing for
+            Err(err) if !recursive => return Err(err),
+            Err(_) => {}
+        }
+    }
+    let write_err = |err: io::Error| err.to_string();
+    for finding in &findings {
+        writeln!(out, "{}", describe(finding)).map_err(write_err)?;
+        if !dry_run {
+            let helper = app.helper_for(exe.as_deref(), finding);
+            scrub::scrub(app.git.as_ref(), finding, helper.as_deref())?;
+        }
+    }
+    let verb = if dry_run { "would be" } else { "were" };
+    writeln!(
+        out,
+        "{} remote urls with tokens {} cleaned in {} repos",
+        findings.len(),
+        verb,
+        repos.len()
+    )
+    .map_err(write_err)
+}
+
+fn rotate_command(app: &mut App, alias: &str, validate: bool) -> Result<(), String> {
+    let idx = app
+        .entries
+        .iter()
+        .position(|entry| entry.alias == alias)
+        .ok_or_else(|| format!("there is no profile {}", alias))?;
+    let mut new_token = String::new();
+    io::stdin()
+        .read_line(&mut new_token)
+        .map_err(|err| err.to_string())?;
+    let summary = app.rotate_token(idx, new_token.trim(), validate)?;
+    for line in summary {
+        println!("{}", line);
+    }
+    Ok(())
+}
+
+// environment variable holding the bundle passphrase, read from stdin otherwise
+const PASSPHRASE_VAR: &str = "GAT_PASSPHRASE";
+
+fn passphrase() -> Result<String, String> {
+    if let Ok(passphrase) = std::env::var(PASSPHRASE_VAR) {
+        return Ok(passphrase);
+    }
+    eprint!("Passphrase: ");
+    let mut passphrase = String::new();
+    io::stdin()
+        .read_line(&mut passphrase)
+        .map_err(|err| err.to_string())?;
+    let passphrase = passphrase.trim_end_matches(['\n', '\r']).to_string();
+    if passphrase.is_empty() {
+        return Err("the passphrase is empty".to_string());
+    }
+    Ok(passphrase)
+}
+
+fn export_command(
+    app: &mut App,
+    file: Option<&Path>,
+    aliases: &[String],
+    tokens: &str,
+) -> Result<(), String> {
+    if let Some(missing) = aliases
+        .iter()
+        .find(|alias| !app.entries.iter().any(|entry| &entry.alias == *alias))
+    {
+        return Err(format!("there is no profile {}", missing));
+    }
+    let entries: Vec<_> = app
+        .entries
+        .iter()
+        .filter(|entry| aliases.is_empty() || aliases.contains(&entry.alias))
+        .cloned()
+        .collect();
+    let mode = match tokens {
+        "plain" => TokenMode::Plain,
+        "encrypt" => TokenMode::Encrypted(passphrase()?),
+        _ => TokenMode::Excluded,
+    };
+    let content = bundle::export(&entries, &mode)?;
+    if let TokenMode::Plain = mode {
+        for entry in entries.iter().filter(|entry| !entry.pa_token.is_empty()) {
+            app.audit(Action::Reveal, &entry.alias, None, "plain export");
+        }
+    }
+    match file {
+        Some(file) => bundle::write_file(file, &content).map_err(|err| err.to_string())?,
+        None => print!("{}", content),
+    }
+    eprintln!("Exported {} profiles", entries.len());
+    Ok(())
+}
+
+fn import_command(app: &mut App, file: &Path, on_conflict: Option<&str>) -> Result<(), String> {
+    let content =
+        std::fs::read_to_string(file).map_err(|err| format!("{}: {}", file.display(), err))?;
+    let passphrase = if bundle::is_encrypted(&content) {
+        Some(passphrase()?)
+    } else {
+        None
+    };
+    let entries = bundle::read(&content, passphrase.as_deref())?;
+    let resolution = on_conflict.and_then(Resolution::from_label);
+    let incoming = bundle::plan_merge(&app.own_entries(), entries, resolution);
+    let conflicts: Vec<&str> = incoming
+        .iter()
+        .filter(|incoming| incoming.conflict)
+        .map(|incoming| incoming.entry.alias.as_str())
+        .collect();
+    if resolution.is_none() && !conflicts.is_empty() {
+        return Err(format!(
+            "profiles {} exist with different content, choose how to handle them with --on-conflict",
+            conflicts.join(", ")
+        ));
+    }
+    let summary = app.import_bundle(incoming);
+    if app.dirty {
+        return Err(app
+            .status
+            .clone()
+            .unwrap_or_else(|| "the profiles could not be saved".to_string()));
+    }
+    println!("{}", summary);
+    Ok(())
+}
+
+/*
+* Run the shell hook (`status --quiet`) and `prompt` without loading the full
+* app, they run on every cd and prompt. None if the full app is needed after
+* all, i.e. for other commands, errors to report or a profile to switch to.
+*/
+pub fn run_hook(
+    command: &Command,
+    store: Option<PathBuf>,
+    shared: Vec<PathBuf>,
+    workdir: &Path,
+    out: &mut dyn Write,
+) -> Option<ExitCode> {
+    let apply = match command {
+        Command::Prompt => None,
+        Command::Status {
+            json: false,
+            quiet: true,
+            apply,
+        } => Some(*apply),
+        _ => return None,
+    };
+    let app = App::for_hook(store, shared, workdir).ok()?;
+    let status = app.as_ref().and_then(shell::repo_status);
+    let written = match (apply, app) {
+        (None, app) => {
+            let prompt = app.map_or_else(String::new, |app| shell::prompt(&app, status.as_ref()));
+            writeln!(out, "{}", prompt).map_err(|err| err.to_string())
+        }
+        (Some(_), None) => Ok(()),
+        (Some(true), Some(_)) if status.as_ref().is_some_and(|status| status.mismatch()) => {
+            return None
+        }
+        (Some(apply), Some(mut app)) => status_command(&mut app, apply, true, out),
+    };
+    Some(match written {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("gat: {}", err);
+            ExitCode::FAILURE
+        }
+    })
+}
+
+/*
+* Report the identity of the target repo. With apply, the bound profile is
+* injected if another identity is in use, which is what the shell hook runs
+* on every directory change.
+*/
+pub fn status_command(
+    app: &mut App,
+    apply: bool,
+    quiet: bool,
+    out: &mut dyn Write,
+) -> Result<(), String> {
+    let write_err = |err: io::Error| err.to_string();
+    let Some(status) = shell::repo_status(app) else {
+        if !quiet {
+            writeln!(out, "{}", app.target_label()).map_err(write_err)?;
+        }
+        return Ok(());
+    };
+    if !quiet {
+        let identity = match (&status.name, &status.email) {
+            (None, None) => "not set".to_string(),
+            (name, email) => format!(
+                "{} <{}>",
+                name.as_deref().unwrap_or(""),
+                email.as_deref().unwrap_or("")
+            ),
+        };
+        let profile = match status.active {
+            Some(idx) => format!("profile {}", app.entries[idx].alias),
+            None => "no profile".to_string(),
+        };
+        let bound = match &status.bound {
+            Some((idx, Binding::Registry(scope))) => format!(
+                "{}, injected here before ({})",
+                app.entries[*idx].alias,
+                scope.label()
+            ),
+            Some((idx, Binding::Remote(host))) => {
+                format!("{}, remote host {}", app.entries[*idx].alias, host)
+            }
+            None => "none".to_string(),
+        };
+        writeln!(out, "{}", app.target_label()).map_err(write_err)?;
+        writeln!(out, "identity: {} ({})", identity, profile).map_err(write_err)?;
+        writeln!(out, "bound: {}", bound).map_err(write_err)?;
+    }
+    let Some((idx, binding)) = status.bound.clone().filter(|_| status.mismatch()) else {
+        return Ok(());
+    };
+    let alias = app.entries[idx].alias.clone();
+    if !apply {
+        return writeln!(
+            out,
+            "gat: {} should use profile {}, run gat status --apply",
+            status.root.display(),
+            alias
+        )
+        .map_err(write_err);
+    }
+    let scope = match binding {
+        Binding::Registry(scope) => scope,
+        Binding::Remote(_) => InjectScope::Local,
+    };
+    let workdir = app.workdir.clone();
+    if !app.inject_profile(idx, scope, &workdir) {
+        return Err(app.status.clone().unwrap_or_default());
+    }
+    writeln!(
+        out,
+        "gat: switched {} to profile {}",
+        status.root.display(),
+        alias
+    )
+    .map_err(write_err)
+}
+
+/*
+* Run the command with the identity and token of the profile, passing on its
+* exit code.
+*/
+fn exec_command(app: &mut App, alias: &str, command: &[String]) -> Result<ExitCode, String> {
+    let workdir = app.workdir.clone();
+    if app.entries.iter().any(|entry| entry.alias == alias) {
+        // only the program, arguments may carry secrets
+        app.audit(Action::Exec, alias, Some(&workdir), &command[0]);
+    }
+    let entry = app
+        .entries
+        .iter()
+        .find(|entry| entry.alias == alias)
+        .ok_or_else(|| format!("there is no profile {}", alias))?;
+    let helper = std::env::current_exe()
+        .ok()
+        .filter(|_| !entry.pa_token.is_empty())
+        .map(|exe| credential::helper_command(&exe, app.store_of(alias), alias, &entry.host));
+    let status = std::process::Command::new(&command[0])
+        .args(&command[1..])
+        .current_dir(&app.workdir)
+        .envs(exec::environment(entry, helper))
+        .status()
+        .map_err(|err| format!("unable to run {}: {}", command[0], err))?;
+    // killed by a signal, 128 plus the signal like a shell reports it
+    #[cfg(unix)]
+    if let Some(signal) = std::os::unix::process::ExitStatusExt::signal(&status) {
+        return Ok(ExitCode::from((128 + signal) as u8));
+    }
+    Ok(ExitCode::from(status.code().unwrap_or(1) as u8))
+}
+
+fn print_json<T: serde::Serialize>(value: &T, out: &mut dyn Write) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(value).map_err(|err| err.to_string())?;
+    writeln!(out, "{}", json).map_err(|err| err.to_string())
+}
+
+fn redacted(token: &str, show_secrets: bool) -> &str {
+    match (token.is_empty(), show_secrets) {
+        (true, _) => "",
+        (false, true) => token,
+        (false, false) => "***",
+    }
+}
+
+pub fn list_command(
+    app: &mut App,
+    json: bool,
+    show_secrets: bool,
+    out: &mut dyn Write,
+) -> Result<(), String> {
+    if show_secrets {
+        let revealed: Vec<String> = app
+            .entries
+            .iter()
+            .filter(|entry| !entry.pa_token.is_empty())
+            .map(|entry| entry.alias.clone())
+            .collect();
+        for alias in revealed {
+            app.audit(Action::Reveal, &alias, None, "gat list");
+        }
+    }
+    if json {
+        let profiles = ProfilesJson {
+            version: JSON_VERSION,
+            profiles: app
+                .entries
+                .iter()
+                .map(|entry| EntryJson::new(entry, show_secrets))
+                .collect(),
+        };
+        return print_json(&profiles, out);
+    }
+    for entry in &app.entries {
+        let mut line = format!("{}\t{} <{}>", entry.alias, entry.author_name(), entry.email);
+        if show_secrets && !entry.pa_token.is_empty() {
+            line.push_str(&format!("\t{}", entry.pa_token));
+        }
+        writeln!(out, "{}", line).map_err(|err| err.to_string())?;
+    }
+    Ok(())
+}
+
+pub fn show_command(
+    app: &mut App,
+    alias: &str,
+    json: bool,
+    show_secrets: bool,
+    out: &mut dyn Write,
+) -> Result<(), String> {
+    let entry = app
+        .entries
+        .iter()
+        .find(|entry| entry.alias == alias)
+        .cloned()
+        .ok_or_else(|| format!("there is no profile {}", alias))?;
+    if show_secrets && !entry.pa_token.is_empty() {
+        app.audit(Action::Reveal, alias, None, "gat show");
+    }
+    if json {
+        let profile = ProfileJson {
+            version: JSON_VERSION,
+            profile: EntryJson::new(&entry, show_secrets),
+        };
+        return print_json(&profile, out);
+    }
+    let mut lines = vec![
+        format!("alias: {}", entry.alias),
+        format!("username: {}", entry.username),
+        format!("email: {}", entry.email),
+        format!("nickname: {}", entry.nickname),
+        format!("token: {}", redacted(&entry.pa_token, show_secrets)),
+    ];
+    if !entry.host.is_empty() {
+        lines.push(format!("host: {}", entry.host));
+    }
+    for (key, value) in &entry.git_config {
+        lines.push(format!("config: {} = {}", key, value));
+    }
+    if let Some(path) = &entry.shared_from {
+        lines.push(format!("shared from: {}", path.display()));
+    }
+    for line in lines {
+        writeln!(out, "{}", line).map_err(|err| err.to_string())?;
+    }
+    Ok(())
+}
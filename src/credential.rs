@@ -0,0 +1,56 @@
+use crate::app::Entry;
+use crate::provider::Provider;
+use std::path::Path;
+
+// quote a word for the shell git runs `!` helpers with
+pub fn shell_quote(word: &str) -> String {
+    format!("'{}'", word.replace('\'', "'\\''"))
+}
+
+/*
+* The credential.helper value making git ask `gat --store <store> credential
+* --host <host> <alias>` for the token of that profile on that host. The store is
+* named so the helper keeps working regardless of the environment git runs in.
+*/
+pub fn helper_command(exe: &Path, store: &Path, alias: &str, host: &str) -> String {
+    format!(
+        "!{} --store {} credential --host {} {}",
+        shell_quote(&exe.to_string_lossy()),
+        shell_quote(&store.to_string_lossy()),
+        shell_quote(host),
+        shell_quote(alias)
+    )
+}
+
+/*
+* Answer a request of the git credential helper protocol. git passes the
+* operation as argument and key=value attributes on stdin. Only `get` is
+* answered since the tokens are managed in gat and never stored by git, and
+* only over https for the host the helper serves: the given one, else the host
+* of the profile. Without either nothing is answered.
+*/
+pub fn respond(
+    entry: Option<&Entry>,
+    operation: &str,
+    attributes: &str,
+    host: Option<&str>,
+) -> String {
+    let Some(entry) = entry.filter(|_| operation == "get") else {
+        return String::new();
+    };
+    let attribute = |name: &str| {
+        attributes
+            .lines()
+            .find_map(|line| line.strip_prefix(name)?.strip_prefix('='))
+    };
+    let host = host.unwrap_or(&entry.host);
+    let requested = attribute("host").unwrap_or_default();
+    if host.is_empty()
+        || attribute("protocol") != Some("https")
+        || !requested.eq_ignore_ascii_case(host)
+    {
+        return String::new();
+    }
+    let (username, password) = Provider::of(entry).credentials(entry);
+    format!("username={}\npassword={}\n", username, password)
+}
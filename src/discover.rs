@@ -0,0 +1,297 @@
+use crate::app::Entry;
+use crate::git::GitRunner;
+use crate::scan;
+use crate::scrub::split_credentials;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+// a profile found on the machine that can be imported
+pub struct Candidate {
+    pub entry: Entry,
+    pub sources: Vec<String>, // where the parts of the profile were found, for display
+    pub selected: bool,
+}
+
+impl Candidate {
+    fn new(entry: Entry, source: String) -> Candidate {
+        Candidate {
+            entry,
+            sources: vec![source],
+            selected: true,
+        }
+    }
+
+    fn add_source(&mut self, source: String) {
+        if !self.sources.contains(&source) {
+            self.sources.push(source);
+        }
+    }
+}
+
+fn config_get(git: &dyn GitRunner, home_dir: &Path, file: &Path, key: &str) -> Option<String> {
+    let file = file.to_string_lossy();
+    let output = git
+        .run(home_dir, &["config", "--file", &file, "--get", key])
+        .ok()?;
+    let value = output.stdout.trim();
+    (output.success && !value.is_empty()).then(|| value.to_string())
+}
+
+// the files included from a config file, with ~ and relative paths resolved
+fn included_files(git: &dyn GitRunner, home_dir: &Path, file: &Path) -> Vec<PathBuf> {
+    let file_arg = file.to_string_lossy();
+    let Ok(output) = git.run(
+        home_dir,
+        &[
+            "config",
+            "--file",
+            &file_arg,
+            "--get-regexp",
+            "^include(if\\..*)?\\.path$",
+        ],
+    ) else {
+        return Vec::new();
+    };
+    output
+        .stdout
+        .lines()
+        .filter_map(|line| line.split_once(' '))
+        .map(|(_, path)| match path.strip_prefix("~/") {
+            Some(rest) => home_dir.join(rest),
+            None => file.parent().unwrap_or(home_dir).join(path),
+        })
+        .collect()
+}
+
+/*
+* Parse the Host blocks of an ssh config into alias -> HostName, e.g.
+* github.com-work -> github.com. Wildcard patterns are skipped.
+*/
+pub fn ssh_hosts(content: &str) -> Vec<(String, String)> {
+    let mut hosts = Vec::new();
+    let mut current: Vec<String> = Vec::new();
+    for line in content.lines() {
+        let line = line.trim();
+        let Some((keyword, value)) = line.split_once(|c: char| c.is_whitespace() || c == '=')
+        else {
+            continue;
+        };
+        let value = value.trim_start_matches(|c: char| c.is_whitespace() || c == '=');
+        match keyword.to_lowercase().as_str() {
+            "host" => {
+                current = value
+                    .split_whitespace()
+                    .filter(|alias| !alias.contains(['*', '?', '!']))
+                    .map(|alias| alias.to_string())
+                    .collect();
+            }
+            "match" => current.clear(),
+            "hostname" => {
+                for alias in current.drain(..) {
+                    if alias != value {
+                        hosts.push((alias, value.to_lowercase()));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    hosts
+}
+
+/*
+* Collects candidates and folds duplicates into each other. Identities are the
+* same if they share an email, credentials if they share host and user.
+*/
+#[derive(Default)]
+struct Collector {
+    candidates: Vec<Candidate>,
+}
+
+impl Collector {
+    fn identity(
+        &mut self,
+        name: Option<String>,
+        email: Option<String>,
+        host: Option<String>,
+        source: String,
+    ) {
+        let Some(email) = email else {
+            return;
+        };
+        let existing = self
+            .candidates
+            .iter_mut()
+            .find(|candidate| candidate.entry.email.eq_ignore_ascii_case(&email));
+        if let Some(candidate) = existing {
+            if candidate.entry.username.is_empty() {
+                candidate.entry.username = name.unwrap_or_default();
+            }
+            if candidate.entry.host.is_empty() {
+                candidate.entry.host = host.unwrap_or_default();
+            }
+            candidate.add_source(source);
+            return;
+        }
+        let entry = Entry {
+            username: name.unwrap_or_default(),
+            email,
+            host: host.unwrap_or_default(),
+            ..Entry::default()
+        };
+        self.candidates.push(Candidate::new(entry, source));
+    }
+
+    /*
+     * A saved credential completes the identity using the same host if that is the
+     * only one without a token. The login becomes the username and the name
+     * from the config the nickname, so commits keep the configured author.
+     */
+    fn credential(&mut self, host: String, user: String, token: String, source: String) {
+        let duplicate = self
+            .candidates
+            .iter_mut()
+            .find(|candidate| candidate.entry.pa_token == token && candidate.entry.host == host);
+        if let Some(candidate) = duplicate {
+            candidate.add_source(source);
+            return;
+        }
+        let mut open: Vec<&mut Candidate> = self
+            .candidates
+            .iter_mut()
+            .filter(|candidate| candidate.entry.host == host && candidate.entry.pa_token.is_empty())
+            .collect();
+        if open.len() == 1 {
+            let candidate = &mut open[0];
+            if candidate.entry.username != user {
+                candidate.entry.nickname = candidate.entry.username.clone();
+                candidate.entry.username = user;
+            }
+            candidate.entry.pa_token = token;
+            candidate.add_source(source);
+            return;
+        }
+        let entry = Entry {
+            username: user,
+            pa_token: token,
+            host,
+            ..Entry::default()
+        };
+        self.candidates.push(Candidate::new(entry, source));
+    }
+
+    fn ssh_host(&mut self, alias: String, host: String, source: String) {
+        if self
+            .candidates
+            .iter()
+            .any(|candidate| candidate.entry.alias == alias)
+        {
+            return;
+        }
+        let entry = Entry {
+            alias,
+            host,
+            ..Entry::default()
+        };
+        self.candidates.push(Candidate::new(entry, source));
+    }
+}
+
+// pick an alias not taken by a profile or another candidate
+pub fn unique_alias(base: &str, taken: &[String]) -> String {
+    let base = if base.is_empty() { "imported" } else { base };
+    if !taken.iter().any(|alias| alias == base) {
+        return base.to_string();
+    }
+    (2..)
+        .map(|n| format!("{}-{}", base, n))
+        .find(|alias| !taken.contains(alias))
+        .unwrap_or_default()
+}
+
+/*
+* Find identities in the global git config and the files it includes,
+* ~/.git-credentials, ~/.ssh/config and, if repo_root is given, the repos below it.
+* Candidates matching an existing profile by email or token are left out.
+*/
+pub fn discover(
+    git: Arc<dyn GitRunner>,
+    home_dir: &Path,
+    repo_root: Option<&Path>,
+    existing: &[Entry],
+) -> Vec<Candidate> {
+    let mut collector = Collector::default();
+    let ssh_config = fs::read_to_string(home_dir.join(".ssh").join("config")).unwrap_or_default();
+    let ssh_hosts: HashMap<String, String> = ssh_hosts(&ssh_config).into_iter().collect();
+
+    let global = home_dir.join(".gitconfig");
+    let mut config_files = vec![global.clone()];
+    config_files.extend(included_files(git.as_ref(), home_dir, &global));
+    for file in config_files.iter().filter(|file| file.exists()) {
+        collector.identity(
+            config_get(git.as_ref(), home_dir, file, "user.name"),
+            config_get(git.as_ref(), home_dir, file, "user.email"),
+            None,
+            file.display().to_string(),
+        );
+    }
+
+    if let Some(root) = repo_root {
+        for repo in scan::scan_all(root, git.clone()) {
+            // remotes through an ssh alias belong to the real host
+            let host = repo
+                .remote_host
+                .map(|host| ssh_hosts.get(&host).cloned().unwrap_or(host));
+            collector.identity(repo.name, repo.email, host, repo.path.display().to_string());
+        }
+    }
+
+    let credentials_file = home_dir.join(".git-credentials");
+    let credentials = fs::read_to_string(&credentials_file).unwrap_or_default();
+    for line in credentials.lines() {
+        let Some((clean_url, user, Some(token))) = split_credentials(line.trim()) else {
+            continue;
+        };
+        let host = scan::remote_host(&clean_url).unwrap_or_default();
+        collector.credential(host, user, token, credentials_file.display().to_string());
+    }
+
+    let ssh_source = home_dir.join(".ssh").join("config").display().to_string();
+    let mut ssh_aliases: Vec<(&String, &String)> = ssh_hosts.iter().collect();
+    ssh_aliases.sort();
+    for (alias, host) in ssh_aliases {
+        collector.ssh_host(alias.clone(), host.clone(), ssh_source.clone());
+    }
+
+    let mut candidates: Vec<Candidate> = collector
+        .candidates
+        .into_iter()
+        .filter(|candidate| {
+            !existing.iter().any(|entry| {
+                (!candidate.entry.email.is_empty()
+                    && entry.email.eq_ignore_ascii_case(&candidate.entry.email))
+                    || (!candidate.entry.pa_token.is_empty()
+                        && entry.pa_token == candidate.entry.pa_token)
+                    || entry.alias == candidate.entry.alias
+            })
+        })
+        .collect();
+    let mut taken: Vec<String> = existing.iter().map(|entry| entry.alias.clone()).collect();
+    for candidate in &mut candidates {
+        if candidate.entry.alias.is_empty() {
+            let base = candidate
+                .entry
+                .email
+                .split('@')
+                .next()
+                .filter(|local| !local.is_empty())
+                .unwrap_or(&candidate.entry.host)
+                .to_lowercase();
+            candidate.entry.alias = unique_alias(&base, &taken);
+        }
+        taken.push(candidate.entry.alias.clone());
+    }
+    candidates
+}
@@ -0,0 +1,45 @@
+use crate::app::Entry;
+use std::env;
+
+// how git is told about config passed through the environment, see git-config(1)
+const CONFIG_COUNT_VAR: &str = "GIT_CONFIG_COUNT";
+
+/*
+* The environment making a child process act as the profile: author and
+* committer identity, the profile's git config overrides and gat as the only
+* credential helper for the host of the profile. The config goes through GIT_CONFIG_COUNT and friends, so
+* nothing is written to any config file. Pairs already set in the parent
+* environment are kept in front.
+*/
+pub fn environment(entry: &Entry, helper: Option<String>) -> Vec<(String, String)> {
+    let name = entry.author_name();
+    let mut vars = vec![
+        ("GIT_AUTHOR_NAME".to_string(), name.clone()),
+        ("GIT_AUTHOR_EMAIL".to_string(), entry.email.clone()),
+        ("GIT_COMMITTER_NAME".to_string(), name),
+        ("GIT_COMMITTER_EMAIL".to_string(), entry.email.clone()),
+    ];
+    let mut config = entry.git_config.clone();
+    // without a host git would hand the token to every server it talks to
+    if let Some(helper) = helper.filter(|_| !entry.host.is_empty()) {
+        let context = format!("credential.https://{}", entry.host);
+        // an empty value drops the helpers configured so far
+        config.push((format!("{}.helper", context), String::new()));
+        config.push((format!("{}.helper", context), helper));
+    }
+    let offset = env::var(CONFIG_COUNT_VAR)
+        .ok()
+        .and_then(|count| count.parse::<usize>().ok())
+        .unwrap_or(0);
+    for (idx, (key, value)) in config.iter().enumerate() {
+        vars.push((format!("GIT_CONFIG_KEY_{}", offset + idx), key.clone()));
+        vars.push((format!("GIT_CONFIG_VALUE_{}", offset + idx), value.clone()));
+    }
+    if !config.is_empty() {
+        vars.push((
+            CONFIG_COUNT_VAR.to_string(),
+            (offset + config.len()).to_string(),
+        ));
+    }
+    vars
+}
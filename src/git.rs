@@ -0,0 +1,34 @@
+use std::io;
+use std::path::Path;
+use std::process::Command;
+
+pub struct GitOutput {
+    pub success: bool,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+/*
+* Everything gat does to repositories goes through this trait, so tests can swap
+* in a runner that records the invocations instead of touching real repos.
+*/
+pub trait GitRunner: Send + Sync {
+    fn run(&self, workdir: &Path, args: &[&str]) -> io::Result<GitOutput>;
+}
+
+// runs the git binary found on the PATH
+pub struct SystemGit;
+
+impl GitRunner for SystemGit {
+    fn run(&self, workdir: &Path, args: &[&str]) -> io::Result<GitOutput> {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(workdir)
+            .output()?;
+        Ok(GitOutput {
+            success: output.status.success(),
+            stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+        })
+    }
+}
@@ -0,0 +1,809 @@
+use ratatui::crossterm::event::{
+    self, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+use std::time::Instant;
+
+use crate::app::{App, CurrentScreen, CurrentlyEditing};
+use crate::mouse::{LastClick, Target};
+
+fn handle_key_press_main(key: KeyEvent, app: &mut App) {
+    match key.code {
+        KeyCode::Char('%') => app.new_profile(),
+        KeyCode::Char('e') => app.edit_selected_entry(),
+        KeyCode::Char('y') => app.duplicate_selected_entry(),
+        KeyCode::Char('J') => app.move_selected(true),
+        KeyCode::Char('K') => app.move_selected(false),
+        KeyCode::Char('f') => app.toggle_pin(),
+        KeyCode::Char('v') => app.toggle_reveal(),
+        KeyCode::Char('S') => app.cycle_sort(),
+        KeyCode::Char('w') => app.open_scrub(),
+        KeyCode::Char('r') => app.open_rotation(),
+        KeyCode::Char('i') => app.open_import(),
+        KeyCode::Char('b') => app.open_bundle(),
+        KeyCode::Char('o') => app.open_repo_picker(),
+        KeyCode::Char('p') => app.open_pairing(),
+        KeyCode::Char('l') => app.open_audit_log(),
+        KeyCode::Char('d') if app.selected_index.is_some() && app.selected_is_writable() => {
+            app.current_screen = CurrentScreen::Deleting
+        }
+        KeyCode::Enter if app.selected_index.is_some() => {
+            app.current_screen = CurrentScreen::Injecting;
+            app.refresh_injection_plan();
+        }
+        KeyCode::Char('j') => match app.selected_index {
+            None => {
+                if !app.entries.is_empty() {
+                    app.selected_index = Some(0)
+                }
+            }
+            Some(selected_index) => {
+                app.selected_index = if selected_index + 1 < app.entries.len() {
+                    Some(selected_index + 1)
+                } else {
+                    Some(app.entries.len() - 1)
+                }
+            }
+        },
+        KeyCode::Char('k') => match app.selected_index {
+            None => {
+                if !app.entries.is_empty() {
+                    app.selected_index = Some(0)
+                }
+            }
+            Some(selected_index) => {
+                app.selected_index = if selected_index > 0 {
+                    Some(selected_index - 1)
+                } else {
+                    Some(0)
+                }
+            }
+        },
+        KeyCode::Char('q') => app.request_quit(),
+        KeyCode::Char('?') => app.current_screen = CurrentScreen::Help,
+        KeyCode::Char('c') => {
+            app.current_screen = CurrentScreen::Cloning;
+        }
+        KeyCode::Char('x') => {
+            app.current_screen = CurrentScreen::Removing;
+            app.refresh_uninject_plan();
+        }
+        KeyCode::Char('g') if app.selected_index.is_some() && app.selected_is_writable() => {
+            app.config_selected = None;
+            app.current_screen = CurrentScreen::ConfigEditor;
+        }
+        KeyCode::Char('s') => app.open_scan(),
+        KeyCode::Char('u') => app.undo(),
+        KeyCode::Char('t') => {
+            app.trash_selected = if app.trash.is_empty() { None } else { Some(0) };
+            app.current_screen = CurrentScreen::Trash;
+        }
+        _ => {}
+    }
+}
+
+fn handle_key_press_config_editor(key: KeyEvent, app: &mut App) {
+    if app.currently_editing.is_some() {
+        match key.code {
+            KeyCode::Enter => {
+                app.add_config_override();
+                app.clear();
+            }
+            KeyCode::Esc => app.clear(),
+            KeyCode::Tab => app.toggle_editing(),
+            _ => {
+                if let Some(input) = app.active_input_mut() {
+                    input.handle_key(key);
+                }
+            }
+        }
+        return;
+    }
+    let count = app
+        .selected_index
+        .map(|idx| app.entries[idx].git_config.len())
+        .unwrap_or(0);
+    match key.code {
+        KeyCode::Char('a') => app.currently_editing = Some(CurrentlyEditing::ConfigKey),
+        KeyCode::Char('d') => app.remove_selected_config_override(),
+        KeyCode::Char('j') if count > 0 => {
+            app.config_selected = Some(
+                app.config_selected
+                    .map_or(0, |idx| (idx + 1).min(count - 1)),
+            );
+        }
+        KeyCode::Char('k') if count > 0 => {
+            app.config_selected = Some(app.config_selected.map_or(0, |idx| idx.saturating_sub(1)));
+        }
+        KeyCode::Esc | KeyCode::Char('q') => app.current_screen = CurrentScreen::Main,
+        _ => {}
+    }
+}
+
+fn handle_key_press_scanning(key: KeyEvent, app: &mut App) {
+    if app.scan_progress.is_none() {
+        match key.code {
+            KeyCode::Enter => app.start_scan(),
+            KeyCode::Esc => app.current_screen = CurrentScreen::Main,
+            _ => {
+                app.scan_root_input.handle_key(key);
+            }
+        }
+        return;
+    }
+    let count = app.scan_results.len();
+    match key.code {
+        KeyCode::Char('j') if count > 0 => {
+            app.scan_selected = Some(app.scan_selected.map_or(0, |idx| (idx + 1).min(count - 1)));
+        }
+        KeyCode::Char('k') if count > 0 => {
+            app.scan_selected = Some(app.scan_selected.map_or(0, |idx| idx.saturating_sub(1)));
+        }
+        KeyCode::Char(' ') => {
+            if let Some(idx) = app.scan_selected {
+                let repo = &mut app.scan_results[idx];
+                repo.selected = !repo.selected && repo.profile.is_some();
+            }
+        }
+        KeyCode::Char('a') => {
+            for repo in app.scan_results.iter_mut() {
+                repo.selected = repo.profile.is_some();
+            }
+        }
+        KeyCode::Char('p') => app.cycle_scan_profile(),
+        KeyCode::Enter => app.inject_scanned(),
+        // go back to choosing a directory, a running scan is dropped
+        KeyCode::Char('r') => {
+            app.scan_receiver = None;
+            app.scan_progress = None;
+        }
+        KeyCode::Esc | KeyCode::Char('q') => app.current_screen = CurrentScreen::Main,
+        _ => {}
+    }
+}
+
+fn handle_key_press_importing(key: KeyEvent, app: &mut App) {
+    let Some(candidates) = app.import_candidates.as_mut() else {
+        match key.code {
+            KeyCode::Enter => app.discover_imports(),
+            KeyCode::Esc => app.current_screen = CurrentScreen::Main,
+            _ => {
+                app.import_root_input.handle_key(key);
+            }
+        }
+        return;
+    };
+    let count = candidates.len();
+    match key.code {
+        KeyCode::Char('j') if count > 0 => {
+            app.import_selected = Some(
+                app.import_selected
+                    .map_or(0, |idx| (idx + 1).min(count - 1)),
+            );
+        }
+        KeyCode::Char('k') if count > 0 => {
+            app.import_selected = Some(app.import_selected.map_or(0, |idx| idx.saturating_sub(1)));
+        }
+        KeyCode::Char(' ') => {
+            if let Some(idx) = app.import_selected {
+                candidates[idx].selected = !candidates[idx].selected;
+            }
+        }
+        KeyCode::Char('a') => {
+            let select = candidates.iter().any(|candidate| !candidate.selected);
+            for candidate in candidates.iter_mut() {
+                candidate.selected = select;
+            }
+        }
+        KeyCode::Enter => app.import_chosen(),
+        KeyCode::Char('r') => app.import_candidates = None,
+        KeyCode::Esc | KeyCode::Char('q') => {
+            app.import_candidates = None;
+            app.current_screen = CurrentScreen::Main;
+        }
+        _ => {}
+    }
+}
+
+fn handle_key_press_bundle(key: KeyEvent, app: &mut App) {
+    let Some(incoming) = app.bundle_incoming.as_ref() else {
+        match key.code {
+            KeyCode::Enter => app.load_bundle(),
+            KeyCode::Esc => app.current_screen = CurrentScreen::Main,
+            _ => {
+                if let Some(input) = app.active_input_mut() {
+                    input.handle_key(key);
+                }
+            }
+        }
+        return;
+    };
+    let count = incoming.len();
+    match key.code {
+        KeyCode::Char('j') if count > 0 => {
+            app.bundle_selected = Some(
+                app.bundle_selected
+                    .map_or(0, |idx| (idx + 1).min(count - 1)),
+            );
+        }
+        KeyCode::Char('k') if count > 0 => {
+            app.bundle_selected = Some(app.bundle_selected.map_or(0, |idx| idx.saturating_sub(1)));
+        }
+        KeyCode::Tab | KeyCode::Char(' ') => app.cycle_bundle_resolution(),
+        KeyCode::Enter => app.merge_bundle(),
+        KeyCode::Esc | KeyCode::Char('q') => {
+            app.bundle_incoming = None;
+            app.current_screen = CurrentScreen::Main;
+        }
+        _ => {}
+    }
+}
+
+fn handle_key_press_trash(key: KeyEvent, app: &mut App) {
+    match key.code {
+        KeyCode::Char('j') => {
+            if let Some(selected_index) = app.trash_selected {
+                if selected_index + 1 < app.trash.len() {
+                    app.trash_selected = Some(selected_index + 1);
+                }
+            }
+        }
+        KeyCode::Char('k') => {
+            if let Some(selected_index) = app.trash_selected {
+                app.trash_selected = Some(selected_index.saturating_sub(1));
+            }
+        }
+        KeyCode::Char('r') => app.restore_selected_trash(),
+        KeyCode::Char('d') => app.purge_selected_trash(),
+        KeyCode::Char('u') => app.undo(),
+        KeyCode::Esc | KeyCode::Char('q') => {
+            app.current_screen = CurrentScreen::Main;
+        }
+        _ => {}
+    }
+}
+
+/*
+* Apply a key press to the app. Sets app.closing if the app is to close.
+*/
+pub fn handle_key_press(key: KeyEvent, app: &mut App) {
+    if key.kind == event::KeyEventKind::Release {
+        // Skip events that are not KeyEventKind::Press
+        return;
+    }
+    if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+        app.request_quit();
+        return;
+    }
+    match app.current_screen {
+        CurrentScreen::Main => handle_key_press_main(key, app),
+        CurrentScreen::Trash => handle_key_press_trash(key, app),
+        CurrentScreen::Scanning => handle_key_press_scanning(key, app),
+        CurrentScreen::Importing => handle_key_press_importing(key, app),
+        CurrentScreen::Bundle => handle_key_press_bundle(key, app),
+        CurrentScreen::ConfigEditor => handle_key_press_config_editor(key, app),
+        CurrentScreen::Removing => match key.code {
+            KeyCode::Tab => app.cycle_inject_scope(),
+            KeyCode::Char('y') => {
+                app.uninject();
+                app.current_screen = CurrentScreen::Main;
+            }
+            KeyCode::Char('n') => app.current_screen = CurrentScreen::Main,
+            _ => {}
+        },
+        CurrentScreen::Help => app.current_screen = CurrentScreen::Main,
+        CurrentScreen::Quitting => match key.code {
+            KeyCode::Char('s') => app.save_and_quit(),
+            KeyCode::Char('d') => app.closing = true,
+            KeyCode::Esc => app.current_screen = app.quit_return_screen,
+            _ => {}
+        },
+        CurrentScreen::Conflict => match key.code {
+            KeyCode::Char('m') => app.merge_external_changes(),
+            KeyCode::Char('r') => app.reload_store(),
+            _ => {}
+        },
+        CurrentScreen::Cloning => match key.code {
+            KeyCode::Enter => {
+                app.clone_repo();
+                app.clear();
+                app.current_screen = CurrentScreen::Main;
+            }
+            KeyCode::Esc => {
+                app.clear();
+                app.current_screen = CurrentScreen::Main;
+            }
+            _ => {
+                app.clone_url_input.handle_key(key);
+            }
+        },
+        CurrentScreen::Deleting => match key.code {
+            KeyCode::Char('y') => {
+                app.current_screen = CurrentScreen::Main;
+                app.delete_current_entry();
+            }
+            KeyCode::Char('n') => {
+                app.current_screen = CurrentScreen::Main;
+            }
+            _ => {}
+        },
+        CurrentScreen::AuditLog => match key.code {
+            KeyCode::Down => {
+                let count = app.filtered_audit().len();
+                app.audit_selected = app
+                    .audit_selected
+                    .map(|idx| (idx + 1).min(count.saturating_sub(1)));
+            }
+            KeyCode::Up => {
+                app.audit_selected = app.audit_selected.map(|idx| idx.saturating_sub(1));
+            }
+            KeyCode::Esc => app.current_screen = CurrentScreen::Main,
+            _ => {
+                if app.audit_filter_input.handle_key(key) {
+                    app.refilter_audit();
+                }
+            }
+        },
+        CurrentScreen::ChoosingTemplate => match key.code {
+            KeyCode::Char('j') | KeyCode::Down => {
+                app.template_selected = app
+                    .template_selected
+                    .map(|idx| (idx + 1).min(app.templates.len()));
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                app.template_selected = app.template_selected.map(|idx| idx.saturating_sub(1));
+            }
+            KeyCode::Enter => app.choose_template(),
+            KeyCode::Char('s') => app.save_template_from_selected(),
+            KeyCode::Char('d') => app.delete_selected_template(),
+            KeyCode::Esc => app.current_screen = CurrentScreen::Main,
+            _ => {}
+        },
+        CurrentScreen::Pairing => match key.code {
+            KeyCode::Char('j') | KeyCode::Down => {
+                if let Some(idx) = app.pairing_selected {
+                    app.pairing_selected = Some((idx + 1).min(app.entries.len() - 1));
+                }
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                app.pairing_selected = app.pairing_selected.map(|idx| idx.saturating_sub(1));
+            }
+            KeyCode::Char(' ') => app.toggle_coauthor(),
+            KeyCode::Enter => app.start_pairing(),
+            KeyCode::Char('e') => app.end_pairing(),
+            KeyCode::Esc => app.current_screen = CurrentScreen::Main,
+            _ => {}
+        },
+        CurrentScreen::ChoosingRepo => match key.code {
+            KeyCode::Tab => app.complete_repo_path(),
+            KeyCode::Enter => app.choose_repo(),
+            KeyCode::Esc => {
+                app.repo_completions.clear();
+                app.current_screen = CurrentScreen::Main;
+            }
+            _ => {
+                app.repo_path_input.handle_key(key);
+            }
+        },
+        CurrentScreen::Rotating if app.rotation_summary.is_empty() => match key.code {
+            KeyCode::Enter => app.rotate_selected_token(),
+            KeyCode::Tab => app.rotate_validate = !app.rotate_validate,
+            KeyCode::Esc => {
+                app.new_token_input.clear();
+                app.current_screen = CurrentScreen::Main;
+            }
+            _ => {
+                app.new_token_input.handle_key(key);
+            }
+        },
+        CurrentScreen::Rotating => match key.code {
+            KeyCode::Enter | KeyCode::Esc => {
+                app.rotation_summary.clear();
+                app.current_screen = CurrentScreen::Main;
+            }
+            _ => {}
+        },
+        CurrentScreen::Scrubbing => match key.code {
+            KeyCode::Tab => app.scrub_helper = !app.scrub_helper,
+            KeyCode::Char('y') => {
+                app.current_screen = CurrentScreen::Main;
+                app.scrub_remotes();
+            }
+            KeyCode::Char('n') | KeyCode::Esc => {
+                app.current_screen = CurrentScreen::Main;
+            }
+            _ => {}
+        },
+        CurrentScreen::Propagating => match key.code {
+            KeyCode::Char('y') => {
+                app.current_screen = CurrentScreen::Main;
+                app.propagate_selected_profile();
+            }
+            KeyCode::Char('n') => {
+                app.current_screen = CurrentScreen::Main;
+            }
+            _ => {}
+        },
+        CurrentScreen::Injecting => match key.code {
+            KeyCode::Tab => app.cycle_inject_scope(),
+            KeyCode::Char('y') => {
+                app.inject_selected_profile();
+                app.current_screen = CurrentScreen::Main;
+            }
+            KeyCode::Char('n') => {
+                app.current_screen = CurrentScreen::Main;
+            }
+            _ => {}
+        },
+        CurrentScreen::Editing if key.kind == KeyEventKind::Press => match key.code {
+            KeyCode::Enter => {
+                app.current_screen = CurrentScreen::Main;
+                app.store_entries();
+                // an invalid field keeps the editor open
+                if app.current_screen != CurrentScreen::Editing {
+                    app.clear();
+                }
+            }
+            KeyCode::Esc => {
+                app.clear();
+                app.current_screen = CurrentScreen::Main;
+                app.currently_editing = None;
+            }
+            KeyCode::Tab => {
+                app.toggle_editing();
+            }
+            _ => {
+                if let Some(input) = app.active_input_mut() {
+                    input.handle_key(key);
+                }
+            }
+        },
+        _ => {}
+    }
+    // a save that ran into an external change wins over the screen the key led to
+    if app.in_conflict {
+        app.current_screen = CurrentScreen::Conflict;
+    }
+}
+
+/*
+* Insert text pasted into the terminal into the focused input, if there is one.
+*/
+pub fn handle_paste(text: &str, app: &mut App) {
+    if let Some(input) = app.active_input_mut() {
+        input.insert_str(text);
+    }
+}
+
+/*
+* Mouse support on top of the keyboard: clicks select profiles, focus inputs and
+* press buttons, a double click on a profile opens the Injecting popup like
+* Enter would and the wheel moves through lists like the arrow keys.
+*/
+pub fn handle_mouse(mouse: MouseEvent, app: &mut App) {
+    let press = |app: &mut App, code: KeyCode| {
+        handle_key_press(KeyEvent::new(code, KeyModifiers::NONE), app)
+    };
+    // j and k would be typed into a focused input
+    let (down, up) = if app.active_input_mut().is_some() {
+        (KeyCode::Down, KeyCode::Up)
+    } else {
+        (KeyCode::Char('j'), KeyCode::Char('k'))
+    };
+    match mouse.kind {
+        MouseEventKind::ScrollDown => press(app, down),
+        MouseEventKind::ScrollUp => press(app, up),
+        MouseEventKind::Down(MouseButton::Left) => {
+            match app.click_map.hit(mouse.column, mouse.row) {
+                Some(Target::ListRow(idx)) if app.current_screen == CurrentScreen::Main => {
+                    let now = Instant::now();
+                    let double = app.last_click.is_some_and(|last| last.is_double(idx, now));
+                    app.selected_index = Some(idx);
+                    if double {
+                        app.last_click = None;
+                        press(app, KeyCode::Enter);
+                    } else {
+                        app.last_click = Some(LastClick { at: now, row: idx });
+                    }
+                }
+                Some(Target::Key(code)) => press(app, code),
+                Some(Target::Field(field)) if app.current_screen == CurrentScreen::Editing => {
+                    app.currently_editing = Some(field)
+                }
+                _ => {}
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::crossterm::event::KeyEventState;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn test_app() -> (TempDir, App) {
+        let dir = tempfile::tempdir().unwrap();
+        let app = App::with_paths(
+            dir.path().to_path_buf(),
+            dir.path().join(".gat"),
+            dir.path().to_path_buf(),
+        );
+        (dir, app)
+    }
+
+    fn press(app: &mut App, code: KeyCode) {
+        handle_key_press(KeyEvent::new(code, KeyModifiers::NONE), app);
+    }
+
+    fn press_ctrl(app: &mut App, c: char) {
+        handle_key_press(KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL), app);
+    }
+
+    fn type_text(app: &mut App, text: &str) {
+        for c in text.chars() {
+            press(app, KeyCode::Char(c));
+        }
+    }
+
+    fn type_profile(app: &mut App, fields: [&str; 5]) {
+        press(app, KeyCode::Char('%'));
+        for (idx, field) in fields.iter().enumerate() {
+            if idx > 0 {
+                press(app, KeyCode::Tab);
+            }
+            type_text(app, field);
+        }
+    }
+
+    fn stored(app: &App) -> String {
+        fs::read_to_string(&app.save_file).unwrap()
+    }
+
+    #[test]
+    fn new_profile_is_saved_once_and_quit_does_not_duplicate_it() {
+        let (_dir, mut app) = test_app();
+        type_profile(&mut app, ["work", "jdoe", "j@doe.dev", "tok", ""]);
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(stored(&app), "work,jdoe,j@doe.dev,tok,\n");
+        assert!(!app.dirty);
+
+        press(&mut app, KeyCode::Char('q'));
+        assert!(app.closing);
+        assert_eq!(app.entries.len(), 1);
+        assert_eq!(stored(&app), "work,jdoe,j@doe.dev,tok,\n");
+    }
+
+    #[test]
+    fn escape_discards_the_profile_in_the_editor() {
+        let (_dir, mut app) = test_app();
+        type_profile(&mut app, ["work", "jdoe", "j@doe.dev", "tok", ""]);
+        press(&mut app, KeyCode::Esc);
+        assert!(app.entries.is_empty());
+        assert!(app.current_screen == CurrentScreen::Main);
+        assert_eq!(stored(&app), "");
+    }
+
+    #[test]
+    fn the_help_lists_the_keys_and_any_key_closes_it() {
+        let (_dir, mut app) = test_app();
+        press(&mut app, KeyCode::Char('?'));
+        assert!(app.current_screen == CurrentScreen::Help);
+        // keys go to the help, not to the main screen behind it
+        press(&mut app, KeyCode::Char('q'));
+        assert!(app.current_screen == CurrentScreen::Main);
+        assert!(!app.closing);
+    }
+
+    #[test]
+    fn ctrl_c_without_changes_quits_right_away() {
+        let (_dir, mut app) = test_app();
+        press_ctrl(&mut app, 'c');
+        assert!(app.closing);
+    }
+
+    #[test]
+    fn ctrl_c_in_editor_prompts_and_discard_keeps_store_untouched() {
+        let (_dir, mut app) = test_app();
+        type_profile(&mut app, ["work", "jdoe", "", "", ""]);
+        press_ctrl(&mut app, 'c');
+        assert!(app.current_screen == CurrentScreen::Quitting);
+        assert!(!app.closing);
+
+        press(&mut app, KeyCode::Char('d'));
+        assert!(app.closing);
+        assert_eq!(stored(&app), "");
+    }
+
+    #[test]
+    fn ctrl_c_in_editor_then_save_stores_the_profile() {
+        let (_dir, mut app) = test_app();
+        type_profile(&mut app, ["work", "jdoe", "j@doe.dev", "tok", "John"]);
+        press_ctrl(&mut app, 'c');
+        press(&mut app, KeyCode::Char('s'));
+        assert!(app.closing);
+        assert_eq!(stored(&app), "work,jdoe,j@doe.dev,tok,John\n");
+    }
+
+    #[test]
+    fn aborting_the_quit_prompt_returns_to_the_editor() {
+        let (_dir, mut app) = test_app();
+        type_profile(&mut app, ["work", "", "", "", ""]);
+        press_ctrl(&mut app, 'c');
+        press(&mut app, KeyCode::Esc);
+        assert!(app.current_screen == CurrentScreen::Editing);
+        assert_eq!(app.alias_input.value(), "work");
+        assert!(!app.closing);
+    }
+
+    #[test]
+    fn deletion_is_persisted_and_can_be_undone() {
+        let (_dir, mut app) = test_app();
+        type_profile(&mut app, ["work", "jdoe", "j@doe.dev", "tok", ""]);
+        press(&mut app, KeyCode::Enter);
+        app.selected_index = Some(0);
+
+        press(&mut app, KeyCode::Char('d'));
+        press(&mut app, KeyCode::Char('y'));
+        assert_eq!(stored(&app), "");
+        assert_eq!(app.trash.len(), 1);
+
+        press(&mut app, KeyCode::Char('u'));
+        assert_eq!(stored(&app), "work,jdoe,j@doe.dev,tok,\n");
+        assert!(app.trash.is_empty());
+    }
+
+    #[test]
+    fn undoing_a_restore_and_then_the_deletion_keeps_a_single_profile() {
+        let (_dir, mut app) = test_app();
+        type_profile(&mut app, ["work", "jdoe", "j@doe.dev", "tok", ""]);
+        press(&mut app, KeyCode::Enter);
+        app.selected_index = Some(0);
+        press(&mut app, KeyCode::Char('d'));
+        press(&mut app, KeyCode::Char('y'));
+
+        press(&mut app, KeyCode::Char('t'));
+        press(&mut app, KeyCode::Char('r'));
+        assert_eq!(stored(&app), "work,jdoe,j@doe.dev,tok,\n");
+        assert!(app.trash.is_empty());
+
+        press(&mut app, KeyCode::Char('u'));
+        assert_eq!(stored(&app), "");
+        assert_eq!(app.trash.len(), 1);
+
+        press(&mut app, KeyCode::Esc);
+        press(&mut app, KeyCode::Char('u'));
+        assert_eq!(stored(&app), "work,jdoe,j@doe.dev,tok,\n");
+        assert!(app.trash.is_empty());
+        press(&mut app, KeyCode::Char('u'));
+        assert_eq!(app.entries.len(), 1);
+    }
+
+    #[test]
+    fn undoing_a_deletion_after_a_restore_does_not_duplicate_the_profile() {
+        let (_dir, mut app) = test_app();
+        type_profile(&mut app, ["work", "jdoe", "j@doe.dev", "tok", ""]);
+        press(&mut app, KeyCode::Enter);
+        app.selected_index = Some(0);
+        press(&mut app, KeyCode::Char('d'));
+        press(&mut app, KeyCode::Char('y'));
+        press(&mut app, KeyCode::Char('t'));
+        press(&mut app, KeyCode::Char('r'));
+        press(&mut app, KeyCode::Esc);
+        // edited after the restore, so undoing the restore leaves it alone
+        press(&mut app, KeyCode::Char('j'));
+        press(&mut app, KeyCode::Char('e'));
+        for _ in 0..3 {
+            press(&mut app, KeyCode::Tab);
+        }
+        type_text(&mut app, "2");
+        press(&mut app, KeyCode::Enter);
+
+        press(&mut app, KeyCode::Char('u'));
+        press(&mut app, KeyCode::Char('u'));
+        assert_eq!(app.entries.len(), 1);
+        assert_eq!(stored(&app), "work,jdoe,j@doe.dev,tok2,\n");
+    }
+
+    #[test]
+    fn undo_restores_the_deleted_profile_among_trashed_ones_with_its_alias() {
+        let (_dir, mut app) = test_app();
+        for token in ["old", "new"] {
+            type_profile(&mut app, ["work", "jdoe", "j@doe.dev", token, ""]);
+            press(&mut app, KeyCode::Enter);
+            app.selected_index = Some(0);
+            press(&mut app, KeyCode::Char('d'));
+            press(&mut app, KeyCode::Char('y'));
+        }
+        // the older profile last, where a lookup by alias would find it
+        app.trash.reverse();
+        assert_eq!(app.trash.len(), 2);
+
+        press(&mut app, KeyCode::Char('u'));
+        assert_eq!(stored(&app), "work,jdoe,j@doe.dev,new,\n");
+        assert_eq!(app.trash.len(), 1);
+        assert_eq!(app.trash[0].entry.pa_token, "old");
+    }
+
+    #[test]
+    fn key_releases_are_ignored() {
+        let (_dir, mut app) = test_app();
+        handle_key_press(
+            KeyEvent {
+                code: KeyCode::Char('%'),
+                modifiers: KeyModifiers::NONE,
+                kind: KeyEventKind::Release,
+                state: KeyEventState::NONE,
+            },
+            &mut app,
+        );
+        assert!(app.current_screen == CurrentScreen::Main);
+    }
+
+    #[test]
+    fn external_change_opens_conflict_and_merge_keeps_both() {
+        let (_dir, mut app) = test_app();
+        fs::write(&app.save_file, "home,me,me@home,t1,\n").unwrap();
+        type_profile(&mut app, ["work", "jdoe", "j@doe.dev", "tok", ""]);
+        press(&mut app, KeyCode::Enter);
+        assert!(app.current_screen == CurrentScreen::Conflict);
+        assert!(app.dirty);
+
+        press(&mut app, KeyCode::Char('m'));
+        assert!(!app.dirty);
+        assert_eq!(
+            stored(&app),
+            "work,jdoe,j@doe.dev,tok,\nhome,me,me@home,t1,\n"
+        );
+    }
+
+    #[test]
+    fn a_conflict_found_while_injecting_is_not_replaced_by_the_main_screen() {
+        let (dir, mut app) = test_app();
+        let init = std::process::Command::new("git")
+            .args(["init", "-q"])
+            .current_dir(dir.path())
+            .status()
+            .expect("git must be installed to run this test");
+        assert!(init.success());
+        type_profile(&mut app, ["work", "jdoe", "j@doe.dev", "tok", ""]);
+        press(&mut app, KeyCode::Enter);
+        fs::write(&app.save_file, "home,me,me@home,t1,\n").unwrap();
+
+        app.selected_index = Some(0);
+        press(&mut app, KeyCode::Enter);
+        press(&mut app, KeyCode::Char('y'));
+        assert!(app.current_screen == CurrentScreen::Conflict);
+        assert!(app.dirty);
+
+        press(&mut app, KeyCode::Char('r'));
+        assert!(app.current_screen == CurrentScreen::Main);
+        assert!(!app.dirty);
+        assert_eq!(stored(&app), "home,me,me@home,t1,\n");
+    }
+
+    #[test]
+    fn config_editor_adds_and_removes_overrides() {
+        let (_dir, mut app) = test_app();
+        type_profile(&mut app, ["work", "jdoe", "j@doe.dev", "tok", ""]);
+        press(&mut app, KeyCode::Enter);
+        app.selected_index = Some(0);
+
+        press(&mut app, KeyCode::Char('g'));
+        press(&mut app, KeyCode::Char('a'));
+        type_text(&mut app, "pull.rebase");
+        press(&mut app, KeyCode::Tab);
+        type_text(&mut app, "true");
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(
+            stored(&app),
+            "work,jdoe,j@doe.dev,tok,,cfg.pull.rebase=true\n"
+        );
+
+        press(&mut app, KeyCode::Char('d'));
+        assert_eq!(stored(&app), "work,jdoe,j@doe.dev,tok,\n");
+        press(&mut app, KeyCode::Esc);
+        assert!(app.current_screen == CurrentScreen::Main);
+    }
+}
@@ -0,0 +1,394 @@
+use crate::app::Entry;
+use crate::git::GitRunner;
+use std::path::{Path, PathBuf};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum InjectScope {
+    Local,    // .git/config of the repository
+    Worktree, // config.worktree of the current linked worktree
+    Global,   // ~/.gitconfig, the default for every repository
+    Include,  // a per profile file included from ~/.gitconfig for this repository only
+}
+
+impl InjectScope {
+    pub const ALL: [InjectScope; 4] = [
+        InjectScope::Local,
+        InjectScope::Worktree,
+        InjectScope::Global,
+        InjectScope::Include,
+    ];
+
+    pub fn next(self) -> InjectScope {
+        match self {
+            InjectScope::Local => InjectScope::Worktree,
+            InjectScope::Worktree => InjectScope::Global,
+            InjectScope::Global => InjectScope::Include,
+            InjectScope::Include => InjectScope::Local,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            InjectScope::Local => "local",
+            InjectScope::Worktree => "worktree",
+            InjectScope::Global => "global",
+            InjectScope::Include => "includeIf",
+        }
+    }
+
+    pub fn from_label(label: &str) -> Option<InjectScope> {
+        InjectScope::ALL
+            .into_iter()
+            .find(|scope| scope.label() == label)
+    }
+}
+
+// a single `git config --file <file> <key> <value>` that an injection will run
+#[derive(Clone, PartialEq, Debug)]
+pub struct ConfigChange {
+    pub file: PathBuf,
+    pub key: String,
+    pub value: String,
+}
+
+impl ConfigChange {
+    fn new(file: &Path, key: &str, value: &str) -> ConfigChange {
+        ConfigChange {
+            file: file.to_path_buf(),
+            key: key.to_string(),
+            value: value.to_string(),
+        }
+    }
+}
+
+fn git_stdout(git: &dyn GitRunner, workdir: &Path, args: &[&str]) -> Option<String> {
+    let output = git.run(workdir, args).ok()?;
+    let stdout = output.stdout.trim();
+    if output.success && !stdout.is_empty() {
+        Some(stdout.to_string())
+    } else {
+        None
+    }
+}
+
+pub fn git_path(git: &dyn GitRunner, workdir: &Path, name: &str) -> Result<PathBuf, String> {
+    git_stdout(
+        git,
+        workdir,
+        &["rev-parse", "--path-format=absolute", "--git-path", name],
+    )
+    .map(PathBuf::from)
+    .ok_or_else(|| format!("{} is not a git repository", workdir.display()))
+}
+
+/*
+* Aliases may come from shared stores and bundles, path separators are escaped so
+* the file always ends up directly in ~/.gat.d and distinct aliases never share one.
+*/
+pub fn include_file(home_dir: &Path, alias: &str) -> PathBuf {
+    let name: String = alias
+        .chars()
+        .map(|c| match c {
+            '%' | '/' | '\\' => format!("%{:02X}", c as u32),
+            c => c.to_string(),
+        })
+        .collect();
+    home_dir.join(".gat.d").join(format!("{}.gitconfig", name))
+}
+
+/*
+* Work out exactly which keys an injection of the entry will write to which file.
+* Nothing is changed yet, the result is shown to the user before confirming.
+*/
+pub fn plan(
+    git: &dyn GitRunner,
+    workdir: &Path,
+    home_dir: &Path,
+    entry: &Entry,
+    scope: InjectScope,
+) -> Result<Vec<ConfigChange>, String> {
+    let identity = [
+        ("user.name", entry.author_name()),
+        ("user.email", entry.email.clone()),
+    ];
+    let global_config = home_dir.join(".gitconfig");
+    let mut changes = Vec::new();
+    let target = match scope {
+        InjectScope::Local => git_path(git, workdir, "config")?,
+        InjectScope::Worktree => {
+            // without this extension git silently writes --worktree keys to .git/config
+            let enabled = git_stdout(
+                git,
+                workdir,
+                &["config", "--get", "--bool", "extensions.worktreeConfig"],
+            );
+            if enabled.as_deref() != Some("true") {
+                let local = git_path(git, workdir, "config")?;
+                changes.push(ConfigChange::new(
+                    &local,
+                    "extensions.worktreeConfig",
+                    "true",
+                ));
+            }
+            git_path(git, workdir, "config.worktree")?
+        }
+        InjectScope::Global => global_config,
+        InjectScope::Include => {
+            let toplevel = git_stdout(git, workdir, &["rev-parse", "--show-toplevel"])
+                .ok_or_else(|| format!("{} is not a git repository", workdir.display()))?;
+            let include = include_file(home_dir, &entry.alias);
+            changes.push(ConfigChange::new(
+                &global_config,
+                &format!("includeIf.gitdir:{}/.path", toplevel),
+                &include.to_string_lossy(),
+            ));
+            include
+        }
+    };
+    for (key, value) in identity {
+        changes.push(ConfigChange::new(&target, key, &value));
+    }
+    for (key, value) in &entry.git_config {
+        changes.push(ConfigChange::new(&target, key, value));
+    }
+    Ok(changes)
+}
+
+// the worktree extension is left alone on removal since other worktrees may rely on it
+const UNTRACKED_KEYS: [&str; 1] = ["extensions.worktreeConfig"];
+
+fn git_config(
+    git: &dyn GitRunner,
+    workdir: &Path,
+    file: &Path,
+    args: &[&str],
+) -> Result<(), String> {
+    let file = file.to_string_lossy();
+    let mut full_args = vec!["config", "--file", &file];
+    full_args.extend_from_slice(args);
+    let output = git
+        .run(workdir, &full_args)
+        .map_err(|err| format!("Unable to run git: {}", err))?;
+    // exit code 5 with no message means an unset key was not set, which is fine
+    if !output.success && !output.stderr.trim().is_empty() {
+        return Err(format!("git config failed: {}", output.stderr.trim()));
+    }
+    Ok(())
+}
+
+// last value of the key in the file, None if it is not set
+fn config_value(git: &dyn GitRunner, workdir: &Path, file: &Path, key: &str) -> Option<String> {
+    if !file.exists() {
+        return None;
+    }
+    let file = file.to_string_lossy();
+    let output = git
+        .run(workdir, &["config", "--file", &file, "--get-all", key])
+        .ok()?;
+    if !output.success {
+        return None;
+    }
+    output.stdout.lines().last().map(|line| line.to_string())
+}
+
+fn marker_key(key: &str, name: &str) -> String {
+    format!("gat.{}.{}", key, name)
+}
+
+/*
+* Write the changes. Next to every key gat records in the same file what it
+* wrote and, on the first injection only, which value it replaced:
+*
+*   [gat]
+*       profile = work
+*   [gat "user.name"]
+*       written = John Doe
+*       previous = Someone Else
+*
+* uninject uses these markers to restore the file to its state before gat.
+*/
+pub fn apply(
+    git: &dyn GitRunner,
+    workdir: &Path,
+    alias: &str,
+    changes: &[ConfigChange],
+) -> Result<(), String> {
+    for change in changes {
+        if let Some(parent) = change.file.parent() {
+            std::fs::create_dir_all(parent).map_err(|err| err.to_string())?;
+        }
+        if !UNTRACKED_KEYS.contains(&change.key.as_str()) {
+            let written = marker_key(&change.key, "written");
+            let tracked = config_value(git, workdir, &change.file, &written).is_some();
+            if !tracked {
+                if let Some(previous) = config_value(git, workdir, &change.file, &change.key) {
+                    git_config(
+                        git,
+                        workdir,
+                        &change.file,
+                        &[&marker_key(&change.key, "previous"), &previous],
+                    )?;
+                }
+            }
+            git_config(git, workdir, &change.file, &[&written, &change.value])?;
+            git_config(git, workdir, &change.file, &["gat.profile", alias])?;
+        }
+        // keys like url.<base>.insteadOf may hold several values
+        git_config(
+            git,
+            workdir,
+            &change.file,
+            &["--replace-all", &change.key, &change.value],
+        )?;
+    }
+    Ok(())
+}
+
+// a key gat wrote and how removing gat from the file will treat it
+#[derive(Clone, PartialEq, Debug)]
+pub struct Restore {
+    pub file: PathBuf,
+    pub key: String,
+    pub previous: Option<String>, // value to restore, None means the key is unset
+    pub modified: bool,           // changed by someone else since, so it is left as is
+}
+
+fn read_markers(git: &dyn GitRunner, workdir: &Path, file: &Path) -> Vec<Restore> {
+    if !file.exists() {
+        return Vec::new();
+    }
+    let file_arg = file.to_string_lossy();
+    let Ok(output) = git.run(
+        workdir,
+        &[
+            "config",
+            "--file",
+            &file_arg,
+            "--get-regexp",
+            "^gat\\..*\\.written$",
+        ],
+    ) else {
+        return Vec::new();
+    };
+    let mut restores = Vec::new();
+    for line in output.stdout.lines() {
+        let (name, written) = line.split_once(' ').unwrap_or((line, ""));
+        let Some(key) = name
+            .strip_prefix("gat.")
+            .and_then(|name| name.strip_suffix(".written"))
+        else {
+            continue;
+        };
+        let current = config_value(git, workdir, file, key);
+        restores.push(Restore {
+            file: file.to_path_buf(),
+            key: key.to_string(),
+            previous: config_value(git, workdir, file, &marker_key(key, "previous")),
+            modified: current.as_deref() != Some(written),
+        });
+    }
+    restores
+}
+
+/*
+* Find the keys gat wrote for the given scope. Only keys belonging to the scope
+* are considered, e.g. removing the includeIf of this repo leaves global keys
+* and the includes of other repos in ~/.gitconfig alone.
+*/
+pub fn plan_uninject(
+    git: &dyn GitRunner,
+    workdir: &Path,
+    home_dir: &Path,
+    scope: InjectScope,
+) -> Result<Vec<Restore>, String> {
+    let global_config = home_dir.join(".gitconfig");
+    let restores = match scope {
+        InjectScope::Local => read_markers(git, workdir, &git_path(git, workdir, "config")?),
+        InjectScope::Worktree => {
+            read_markers(git, workdir, &git_path(git, workdir, "config.worktree")?)
+        }
+        InjectScope::Global => read_markers(git, workdir, &global_config)
+            .into_iter()
+            .filter(|restore| !restore.key.starts_with("includeIf."))
+            .collect(),
+        InjectScope::Include => {
+            let toplevel = git_stdout(git, workdir, &["rev-parse", "--show-toplevel"])
+                .ok_or_else(|| format!("{} is not a git repository", workdir.display()))?;
+            let include_key = format!("includeIf.gitdir:{}/.path", toplevel);
+            read_markers(git, workdir, &global_config)
+                .into_iter()
+                .filter(|restore| restore.key == include_key)
+                .collect()
+        }
+    };
+    Ok(restores)
+}
+
+/*
+* The profile the planned keys were written for. For an include it is the one of
+* the included file, ~/.gitconfig only names the profile included last.
+*/
+pub fn injected_profile(
+    git: &dyn GitRunner,
+    workdir: &Path,
+    restores: &[Restore],
+) -> Option<String> {
+    let restore = restores.first()?;
+    let file = if restore.key.starts_with("includeIf.") {
+        PathBuf::from(config_value(git, workdir, &restore.file, &restore.key)?)
+    } else {
+        restore.file.clone()
+    };
+    config_value(git, workdir, &file, "gat.profile")
+}
+
+/*
+* The keys gat wrote before that the new changes do not write again, e.g. the
+* overrides of the profile injected previously or one deleted since. They are
+* removed before applying, so nothing of the old profile stays behind.
+*/
+pub fn stale(restores: Vec<Restore>, changes: &[ConfigChange]) -> Vec<Restore> {
+    restores
+        .into_iter()
+        .filter(|restore| {
+            !changes
+                .iter()
+                .any(|change| change.file == restore.file && change.key == restore.key)
+        })
+        .collect()
+}
+
+/*
+* Put back the previous values of the planned keys, or unset keys that had none,
+* and drop the markers. Keys modified since injection keep their value.
+*/
+pub fn uninject(git: &dyn GitRunner, workdir: &Path, restores: &[Restore]) -> Result<(), String> {
+    for restore in restores {
+        if !restore.modified {
+            match &restore.previous {
+                Some(previous) => git_config(
+                    git,
+                    workdir,
+                    &restore.file,
+                    &["--replace-all", &restore.key, previous],
+                )?,
+                None => git_config(git, workdir, &restore.file, &["--unset-all", &restore.key])?,
+            }
+        }
+        git_config(
+            git,
+            workdir,
+            &restore.file,
+            &["--remove-section", &format!("gat.{}", restore.key)],
+        )?;
+    }
+    // forget the profile once no tracked key is left in a file
+    let mut files: Vec<&PathBuf> = restores.iter().map(|restore| &restore.file).collect();
+    files.dedup();
+    for file in files {
+        if read_markers(git, workdir, file).is_empty() {
+            git_config(git, workdir, file, &["--unset", "gat.profile"])?;
+        }
+    }
+    Ok(())
+}
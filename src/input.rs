@@ -0,0 +1,338 @@
+use ratatui::{
+    crossterm::event::{KeyCode, KeyEvent, KeyModifiers},
+    layout::{Position, Rect},
+    widgets::{Block, Paragraph},
+    Frame,
+};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/*
+* A single line text input with a cursor.
+* The cursor is stored as a char index into the value, so multi byte characters
+* never get split when editing.
+*/
+#[derive(Default, Clone)]
+pub struct TextInput {
+    value: String,
+    cursor: usize,
+    masked: bool, // rendered as asterisks, for passphrases
+}
+
+impl TextInput {
+    pub fn masked() -> TextInput {
+        TextInput {
+            masked: true,
+            ..TextInput::default()
+        }
+    }
+
+    pub fn set_masked(&mut self, masked: bool) {
+        self.masked = masked;
+    }
+
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    pub fn clear(&mut self) {
+        self.value.clear();
+        self.cursor = 0;
+    }
+
+    // replace the value, placing the cursor at its end
+    pub fn set(&mut self, value: &str) {
+        self.value = value.to_string();
+        self.cursor = self.len();
+    }
+
+    pub fn move_to_start(&mut self) {
+        self.cursor = 0;
+    }
+
+    fn len(&self) -> usize {
+        self.value.chars().count()
+    }
+
+    fn byte_index(&self, char_idx: usize) -> usize {
+        self.value
+            .char_indices()
+            .nth(char_idx)
+            .map(|(idx, _)| idx)
+            .unwrap_or(self.value.len())
+    }
+
+    pub fn insert(&mut self, c: char) {
+        let idx = self.byte_index(self.cursor);
+        self.value.insert(idx, c);
+        self.cursor += 1;
+    }
+
+    /*
+     * Insert pasted text at the cursor. Line breaks are dropped since all inputs
+     * are single line and a trailing newline is common when copying tokens.
+     */
+    pub fn insert_str(&mut self, text: &str) {
+        for c in text.chars().filter(|c| *c != '\n' && *c != '\r') {
+            self.insert(c);
+        }
+    }
+
+    fn remove_range(&mut self, from: usize, to: usize) {
+        let start = self.byte_index(from);
+        let end = self.byte_index(to);
+        self.value.replace_range(start..end, "");
+        self.cursor = from;
+    }
+
+    // char index of the start of the word left of the cursor
+    fn word_start(&self) -> usize {
+        let chars: Vec<char> = self.value.chars().collect();
+        let mut idx = self.cursor;
+        while idx > 0 && chars[idx - 1].is_whitespace() {
+            idx -= 1;
+        }
+        while idx > 0 && !chars[idx - 1].is_whitespace() {
+            idx -= 1;
+        }
+        idx
+    }
+
+    // char index of the end of the word right of the cursor
+    fn word_end(&self) -> usize {
+        let chars: Vec<char> = self.value.chars().collect();
+        let mut idx = self.cursor;
+        while idx < chars.len() && chars[idx].is_whitespace() {
+            idx += 1;
+        }
+        while idx < chars.len() && !chars[idx].is_whitespace() {
+            idx += 1;
+        }
+        idx
+    }
+
+    /*
+     * Apply a key press to the input.
+     * Returns true if the key was consumed, false if the caller should handle it.
+     */
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        let alt = key.modifiers.contains(KeyModifiers::ALT);
+        match key.code {
+            KeyCode::Char('a') if ctrl => self.cursor = 0,
+            KeyCode::Char('e') if ctrl => self.cursor = self.len(),
+            KeyCode::Char('w') if ctrl => self.remove_range(self.word_start(), self.cursor),
+            KeyCode::Char('u') if ctrl => self.remove_range(0, self.cursor),
+            KeyCode::Char('k') if ctrl => {
+                let cursor = self.cursor;
+                self.remove_range(cursor, self.len());
+            }
+            KeyCode::Char(_) if ctrl => return false,
+            KeyCode::Char(value) => self.insert(value),
+            KeyCode::Backspace if ctrl || alt => self.remove_range(self.word_start(), self.cursor),
+            KeyCode::Backspace => {
+                if self.cursor > 0 {
+                    self.remove_range(self.cursor - 1, self.cursor);
+                }
+            }
+            KeyCode::Delete => {
+                if self.cursor < self.len() {
+                    self.remove_range(self.cursor, self.cursor + 1);
+                }
+            }
+            KeyCode::Left if ctrl || alt => self.cursor = self.word_start(),
+            KeyCode::Right if ctrl || alt => self.cursor = self.word_end(),
+            KeyCode::Left => self.cursor = self.cursor.saturating_sub(1),
+            KeyCode::Right => self.cursor = (self.cursor + 1).min(self.len()),
+            KeyCode::Home => self.cursor = 0,
+            KeyCode::End => self.cursor = self.len(),
+            _ => return false,
+        }
+        true
+    }
+
+    /*
+     * Render the input into the given block.
+     * Values wider than the block are scrolled horizontally so the cursor always
+     * stays visible.
+     */
+    pub fn render(&self, frame: &mut Frame, area: Rect, block: Block, focused: bool) {
+        let inner = block.inner(area);
+        let width = inner.width as usize;
+        let scroll = if width == 0 {
+            0
+        } else {
+            self.cursor.saturating_sub(width - 1)
+        };
+        let visible: String = self
+            .value
+            .chars()
+            .skip(scroll)
+            .take(width)
+            .map(|c| if self.masked { '*' } else { c })
+            .collect();
+        frame.render_widget(Paragraph::new(visible).block(block), area);
+        if focused && inner.width > 0 && inner.height > 0 {
+            frame.set_cursor_position(Position::new(
+                inner.x + (self.cursor - scroll) as u16,
+                inner.y,
+            ));
+        }
+    }
+}
+
+// a path typed by the user with a leading ~ resolved to the home directory
+pub fn expand_home(path: &str, home_dir: &Path) -> PathBuf {
+    match path.strip_prefix('~') {
+        Some("") => home_dir.to_path_buf(),
+        Some(rest) if rest.starts_with('/') => home_dir.join(&rest[1..]),
+        _ => PathBuf::from(path),
+    }
+}
+
+/*
+* Complete the last component of a directory path as far as it is unambiguous,
+* like a shell does on Tab. Returns the completed value and the names of all
+* matching directories. Hidden directories only match a prefix starting with '.'.
+*/
+pub fn complete_path(value: &str, home_dir: &Path) -> (String, Vec<String>) {
+    let (dir_part, prefix) = match value.rfind('/') {
+        Some(idx) => value.split_at(idx + 1),
+        None => ("", value),
+    };
+    let dir = if dir_part.is_empty() {
+        PathBuf::from(".")
+    } else {
+        expand_home(dir_part, home_dir)
+    };
+    let mut matches: Vec<String> = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| {
+            name.starts_with(prefix) && (prefix.starts_with('.') || !name.starts_with('.'))
+        })
+        .collect();
+    matches.sort();
+    let completed = match matches.as_slice() {
+        [] => value.to_string(),
+        [only] => format!("{}{}/", dir_part, only),
+        [first, rest @ ..] => {
+            let common = rest.iter().fold(first.clone(), |common, name| {
+                common
+                    .chars()
+                    .zip(name.chars())
+                    .take_while(|(a, b)| a == b)
+                    .map(|(a, _)| a)
+                    .collect()
+            });
+            format!("{}{}", dir_part, common)
+        }
+    };
+    (completed, matches)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::{backend::TestBackend, widgets::Borders, Terminal};
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn ctrl(c: char) -> KeyEvent {
+        KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL)
+    }
+
+    fn typed(text: &str) -> TextInput {
+        let mut input = TextInput::default();
+        for c in text.chars() {
+            input.handle_key(key(KeyCode::Char(c)));
+        }
+        input
+    }
+
+    // the visible text and cursor column of an input rendered without borders
+    fn rendered(input: &TextInput, width: u16) -> (String, u16) {
+        let mut terminal = Terminal::new(TestBackend::new(width, 1)).unwrap();
+        terminal
+            .draw(|frame| {
+                let area = frame.area();
+                input.render(frame, area, Block::default().borders(Borders::NONE), true)
+            })
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        let text = (0..width)
+            .map(|column| buffer[(column, 0)].symbol())
+            .collect::<String>();
+        let cursor = terminal.get_cursor_position().unwrap();
+        (text.trim_end().to_string(), cursor.x)
+    }
+
+    #[test]
+    fn the_cursor_moves_over_multibyte_characters() {
+        let mut input = typed("zürich");
+        input.handle_key(key(KeyCode::Home));
+        input.handle_key(key(KeyCode::Right));
+        input.handle_key(key(KeyCode::Right));
+        input.handle_key(key(KeyCode::Backspace));
+        assert_eq!(input.value(), "zrich");
+        input.handle_key(key(KeyCode::Char('ö')));
+        input.handle_key(key(KeyCode::Delete));
+        assert_eq!(input.value(), "zöich");
+        input.handle_key(key(KeyCode::End));
+        input.handle_key(key(KeyCode::Char('ß')));
+        assert_eq!(input.value(), "zöichß");
+    }
+
+    #[test]
+    fn ctrl_w_deletes_the_word_left_of_the_cursor() {
+        let mut input = typed("git push  origin");
+        input.handle_key(ctrl('w'));
+        assert_eq!(input.value(), "git push  ");
+        input.handle_key(ctrl('w'));
+        assert_eq!(input.value(), "git ");
+
+        let mut input = typed("one two three");
+        input.handle_key(KeyEvent::new(KeyCode::Left, KeyModifiers::CONTROL));
+        input.handle_key(ctrl('w'));
+        assert_eq!(input.value(), "one three");
+        // unbound control keys are left to the caller
+        assert!(!input.handle_key(ctrl('x')));
+    }
+
+    #[test]
+    fn pastes_drop_line_breaks() {
+        let mut input = typed("ab");
+        input.handle_key(key(KeyCode::Left));
+        input.insert_str("ghp_1\r\n23\n");
+        assert_eq!(input.value(), "aghp_123b");
+        input.handle_key(key(KeyCode::Char('!')));
+        assert_eq!(input.value(), "aghp_123!b");
+    }
+
+    #[test]
+    fn masked_inputs_render_asterisks() {
+        let mut input = TextInput::masked();
+        input.set("sécret");
+        assert_eq!(input.value(), "sécret");
+        assert_eq!(rendered(&input, 10), ("******".to_string(), 6));
+    }
+
+    #[test]
+    fn long_values_scroll_to_keep_the_cursor_visible() {
+        let input = typed("abcdefghij");
+        assert_eq!(rendered(&input, 5), ("ghij".to_string(), 4));
+
+        let mut input = input;
+        input.handle_key(key(KeyCode::Home));
+        assert_eq!(rendered(&input, 5), ("abcde".to_string(), 0));
+        for _ in 0..6 {
+            input.handle_key(key(KeyCode::Right));
+        }
+        assert_eq!(rendered(&input, 5), ("cdefg".to_string(), 4));
+    }
+}
@@ -0,0 +1,25 @@
+pub mod app;
+pub mod audit;
+pub mod bundle;
+pub mod cli;
+pub mod credential;
+pub mod discover;
+pub mod exec;
+pub mod git;
+pub mod handler;
+pub mod inject;
+pub mod input;
+pub mod main_menu;
+pub mod mouse;
+pub mod pair;
+pub mod popups;
+pub mod provider;
+pub mod registry;
+pub mod rotate;
+pub mod scan;
+pub mod scrub;
+pub mod shell;
+pub mod store;
+pub mod template;
+pub mod trash;
+pub mod ui;
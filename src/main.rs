@@ -1,31 +1,47 @@
-use std::{error::Error, io};
+use std::{
+    error::Error,
+    io,
+    path::PathBuf,
+    process::ExitCode,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
 
+use clap::Parser;
 use ratatui::{
     backend::{Backend, CrosstermBackend},
     crossterm::{
         event::{
-            self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyEventKind,
+            self, DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste,
+            EnableMouseCapture, Event,
         },
         execute,
         terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
     },
     Terminal,
 };
+use signal_hook::consts::{SIGINT, SIGTERM};
 
-mod app;
-mod main_menu;
-mod popups;
-mod ui;
-
-use crate::{
-    app::{App, CurrentScreen, CurrentlyEditing},
+use gat::{
+    app::{App, CurrentScreen},
+    cli::{self, Cli},
+    handler::{handle_key_press, handle_mouse, handle_paste},
     ui::ui,
 };
 
 fn init_terminal() -> Terminal<CrosstermBackend<std::io::Stdout>> {
     enable_raw_mode().unwrap();
     let mut stdout = std::io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture).unwrap();
+    execute!(
+        stdout,
+        EnterAlternateScreen,
+        EnableMouseCapture,
+        EnableBracketedPaste
+    )
+    .unwrap();
     let backend = CrosstermBackend::new(stdout);
     Terminal::new(backend).unwrap()
 }
@@ -35,214 +51,102 @@ fn restore_terminal(terminal: &mut Terminal<CrosstermBackend<std::io::Stdout>>)
     execute!(
         terminal.backend_mut(),
         LeaveAlternateScreen,
-        DisableMouseCapture
+        DisableMouseCapture,
+        DisableBracketedPaste
     )
     .unwrap();
     terminal.show_cursor().unwrap();
 }
 
-fn main() -> Result<(), Box<dyn Error>> {
+fn main() -> Result<ExitCode, Box<dyn Error>> {
+    let args = Cli::parse();
+    let workdir = args.directory.unwrap_or_else(|| PathBuf::from("."));
+    if let Some(command) = &args.command {
+        let hook = cli::run_hook(
+            command,
+            args.store.clone(),
+            args.shared.clone(),
+            &workdir,
+            &mut io::stdout(),
+        );
+        if let Some(code) = hook {
+            return Ok(code);
+        }
+    }
+    let mut app = match App::new(args.store, args.shared) {
+        Ok(app) => app,
+        Err(err) => {
+            eprintln!("gat: {}", err);
+            return Ok(ExitCode::FAILURE);
+        }
+    };
+    if let Err(err) = app.set_workdir(&workdir) {
+        eprintln!("gat: {}", err);
+        return Ok(ExitCode::FAILURE);
+    }
+    if let Some(command) = args.command {
+        return Ok(cli::run(command, &mut app));
+    }
+
+    // SIGINT and SIGTERM only raise this flag, the event loop then saves and exits normally
+    let terminate = Arc::new(AtomicBool::new(false));
+    signal_hook::flag::register(SIGINT, Arc::clone(&terminate))?;
+    signal_hook::flag::register(SIGTERM, Arc::clone(&terminate))?;
+
     let mut terminal = init_terminal();
 
-    // create app and run it
-    let mut app = App::new();
-    let res = run_app(&mut terminal, &mut app);
+    let res = run_app(&mut terminal, &mut app, &terminate);
 
     restore_terminal(&mut terminal);
 
-    if let Ok(do_print) = res {
-        if do_print {
-            app.store_entries();
-        }
-    } else if let Err(err) = res {
+    if let Err(err) = res {
         println!("{err:?}");
     }
-    Ok(())
-}
-
-fn handle_key_press_main(key: KeyEvent, app: &mut App) {
-    match key.code {
-        KeyCode::Char('%') => {
-            app.current_screen = CurrentScreen::Editing;
-            app.currently_editing = Some(CurrentlyEditing::Alias);
-        }
-        KeyCode::Char('d') => match app.selected_index {
-            Some(_) => app.current_screen = CurrentScreen::Deleting,
-            _ => {}
-        },
-        KeyCode::Enter => match app.selected_index {
-            Some(_) => app.current_screen = CurrentScreen::Injecting,
-            _ => {}
-        },
-        KeyCode::Char('j') => match app.selected_index {
-            None => {
-                if app.entries.len() > 0 {
-                    app.selected_index = Some(0)
-                }
-            }
-            Some(selected_index) => {
-                app.selected_index = if selected_index + 1 < app.entries.len() {
-                    Some(selected_index + 1)
-                } else {
-                    Some(app.entries.len() - 1)
-                }
-            }
-        },
-        KeyCode::Char('k') => match app.selected_index {
-            None => {
-                if app.entries.len() > 0 {
-                    app.selected_index = Some(0)
-                }
-            }
-            Some(selected_index) => {
-                app.selected_index = if selected_index > 0 {
-                    Some(selected_index - 1)
-                } else {
-                    Some(0)
-                }
-            }
-        },
-        KeyCode::Char('q') => {
-            app.save_all_data();
-            app.closing = true;
-        }
-        KeyCode::Char('c') => {
-            app.current_screen = CurrentScreen::Cloning;
-        }
-        _ => {}
+    if app.dirty {
+        println!(
+            "Some changes could not be saved to {}",
+            app.save_file.display()
+        );
     }
+    Ok(ExitCode::SUCCESS)
 }
 
-/*
-* Returns true if the app is to close after the key press,
-* return false otherwise.
-*/
-fn handle_key_press(key: KeyEvent, app: &mut App) {
-    if key.kind == event::KeyEventKind::Release {
-        // Skip events that are not KeyEventKind::Press
-        return;
-    }
-    match app.current_screen {
-        CurrentScreen::Main => handle_key_press_main(key, app),
-        CurrentScreen::Cloning => match key.code {
-            KeyCode::Enter => {
-                app.clone_repo();
-                app.clear();
-                app.current_screen = CurrentScreen::Main;
-            }
-            KeyCode::Backspace => {
-                app.clone_url_input.pop();
-            }
-            KeyCode::Esc => {
-                app.clear();
-                app.current_screen = CurrentScreen::Main;
-            }
-            KeyCode::Char(value) => {
-                app.clone_url_input.push(value);
-            }
-            _ => {}
-        },
-        CurrentScreen::Deleting => match key.code {
-            KeyCode::Char('y') => {
-                app.delete_current_entry();
-                app.current_screen = CurrentScreen::Main;
-            }
-            KeyCode::Char('n') => {
-                app.current_screen = CurrentScreen::Main;
-            }
-            _ => {}
-        },
-        CurrentScreen::Injecting => match key.code {
-            KeyCode::Char('y') => {
-                app.inject_selected_profile();
-                app.current_screen = CurrentScreen::Main;
-            }
-            KeyCode::Char('n') => {
-                app.current_screen = CurrentScreen::Main;
-            }
-            _ => {}
-        },
-        CurrentScreen::Editing if key.kind == KeyEventKind::Press => match key.code {
-            KeyCode::Enter => {
-                app.store_entries();
-                app.clear();
-                app.current_screen = CurrentScreen::Main;
-            }
-            KeyCode::Backspace => {
-                if let Some(editing) = &app.currently_editing {
-                    match editing {
-                        CurrentlyEditing::Alias => {
-                            app.alias_input.pop();
-                        }
-                        CurrentlyEditing::Username => {
-                            app.username_input.pop();
-                        }
-                        CurrentlyEditing::Email => {
-                            app.email_input.pop();
-                        }
-                        CurrentlyEditing::Token => {
-                            app.token_input.pop();
-                        }
-                        CurrentlyEditing::Nickname => {
-                            app.nickname_input.pop();
-                        }
-                    }
-                }
-            }
-            KeyCode::Esc => {
-                app.clear();
-                app.current_screen = CurrentScreen::Main;
-                app.currently_editing = None;
-            }
-            KeyCode::Tab => {
-                app.toggle_editing();
-            }
-            KeyCode::Char(value) => {
-                if let Some(editing) = &app.currently_editing {
-                    match editing {
-                        CurrentlyEditing::Alias => {
-                            app.alias_input.push(value);
-                        }
-                        CurrentlyEditing::Username => {
-                            app.username_input.push(value);
-                        }
-                        CurrentlyEditing::Email => {
-                            app.email_input.push(value);
-                        }
-                        CurrentlyEditing::Token => {
-                            app.token_input.push(value);
-                        }
-                        CurrentlyEditing::Nickname => {
-                            app.nickname_input.push(value);
-                        }
-                    }
-                }
+fn run_app<B: Backend>(
+    terminal: &mut Terminal<B>,
+    app: &mut App,
+    terminate: &AtomicBool,
+) -> io::Result<()> {
+    loop {
+        if terminate.load(Ordering::Relaxed) {
+            // no one is around to answer a prompt, save what can be saved and leave
+            if app.dirty {
+                app.save_all_data();
             }
-            _ => {}
-        },
-        _ => {}
-    }
-}
+            return Ok(());
+        }
 
-fn run_app<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> io::Result<bool> {
-    loop {
         // adjust rendering params specific to the view
-        match app.current_screen {
-            CurrentScreen::Main => {
-                if app.selected_index == None && app.entries.len() > 0 {
-                    app.selected_index = Some(0);
-                }
+        if let CurrentScreen::Main = app.current_screen {
+            if app.selected_index.is_none() && !app.entries.is_empty() {
+                app.selected_index = Some(0);
             }
-            _ => {}
         }
 
+        app.poll_scan();
         terminal.draw(|f| ui(f, app))?;
 
-        if let Event::Key(key) = event::read()? {
-            handle_key_press(key, app);
-            if app.closing {
-                return Ok(true);
-            }
+        // wake up regularly to notice termination signals
+        if !event::poll(Duration::from_millis(250))? {
+            continue;
+        }
+        match event::read()? {
+            Event::Key(key) => handle_key_press(key, app),
+            Event::Paste(text) => handle_paste(&text, app),
+            Event::Mouse(mouse) => handle_mouse(mouse, app),
+            _ => {}
+        }
+        if app.closing {
+            return Ok(());
         }
     }
 }
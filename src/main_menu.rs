@@ -1,4 +1,5 @@
 use crate::app::{App, CurrentScreen, CurrentlyEditing};
+use crate::mouse::Target;
 use crate::ui::key_hints;
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
@@ -14,7 +15,9 @@ fn current_navigation_text<'a>(app: &App) -> Vec<Span<'a>> {
     menu_items.push(
         match &app.current_screen {
             CurrentScreen::Main => Span::styled("Normal Mode", Style::default().fg(Color::Green)),
-            CurrentScreen::Editing => {
+            CurrentScreen::Editing | CurrentScreen::ConfigEditor
+                if app.currently_editing.is_some() =>
+            {
                 Span::styled("Editing Mode", Style::default().fg(Color::Yellow))
             }
             _ => Span::styled("Normal Mode", Style::default().fg(Color::Green)),
@@ -40,11 +43,30 @@ fn current_navigation_text<'a>(app: &App) -> Vec<Span<'a>> {
                 CurrentlyEditing::Nickname => {
                     Span::styled("Editing nickname", Style::default().fg(Color::Green))
                 }
+                CurrentlyEditing::Host => {
+                    Span::styled("Editing host", Style::default().fg(Color::Green))
+                }
+                CurrentlyEditing::Provider => {
+                    Span::styled("Editing provider", Style::default().fg(Color::Green))
+                }
+                CurrentlyEditing::ConfigKey => {
+                    Span::styled("Editing config key", Style::default().fg(Color::Green))
+                }
+                CurrentlyEditing::ConfigValue => {
+                    Span::styled("Editing config value", Style::default().fg(Color::Green))
+                }
             }
         } else {
             Span::styled("Not Editing Anything", Style::default().fg(Color::DarkGray))
         }
     });
+    if let Some(status) = &app.status {
+        menu_items.push(Span::styled(" | ", Style::default().fg(Color::White)));
+        menu_items.push(Span::styled(
+            status.clone(),
+            Style::default().fg(Color::Red),
+        ));
+    }
     menu_items
 }
 
@@ -87,7 +109,14 @@ pub fn render_title(title: &str, frame: &mut Frame, area: &Rect) {
 
 pub fn render_main_menu(frame: &mut Frame, app: &App) {
     let chunks = split_main_frame(frame);
-    render_title("Manage Git Profiles and Access Tokens", frame, &chunks[0]);
+    let mut title = format!(
+        "Manage Git Profiles and Access Tokens | {}",
+        app.target_label()
+    );
+    if !app.coauthors.is_empty() {
+        title.push_str(&format!(" | pairing with {}", app.coauthors.len()));
+    }
+    render_title(&title, frame, &chunks[0]);
     render_list(frame, &chunks[1], app);
     render_footer(frame, app, &chunks[2]);
 }
@@ -98,10 +127,18 @@ pub fn render_main_menu(frame: &mut Frame, app: &App) {
 fn render_list(frame: &mut Frame, area: &Rect, app: &App) {
     let mut list_items = Vec::<ListItem>::new();
     for entry in &app.entries {
-        list_items.push(ListItem::new(Line::from(Span::styled(
-            format!("{}", entry.alias),
-            Style::default().fg(Color::Yellow),
-        ))));
+        let mut spans = vec![
+            Span::raw(if entry.pinned { "* " } else { "  " }),
+            Span::styled(entry.alias.clone(), Style::default().fg(Color::Yellow)),
+        ];
+        if let Some(path) = &entry.shared_from {
+            let name = path.file_name().unwrap_or(path.as_os_str());
+            spans.push(Span::styled(
+                format!(" (shared: {})", name.to_string_lossy()),
+                Style::default().fg(Color::DarkGray),
+            ));
+        }
+        list_items.push(ListItem::new(Line::from(spans)));
     }
 
     let main_chunks = Layout::default()
@@ -115,4 +152,12 @@ fn render_list(frame: &mut Frame, area: &Rect, app: &App) {
         .block(Block::default().borders(Borders::LEFT));
     frame.render_stateful_widget(list, main_chunks[0], &mut list_state);
     frame.render_widget(profile_content, main_chunks[1]);
+
+    // every visible row, rendering has scrolled the list to keep the selection in view
+    let list_area = main_chunks[0];
+    let visible = (list_state.offset()..app.entries.len()).take(list_area.height as usize);
+    for (row, idx) in visible.enumerate() {
+        let row_area = Rect::new(list_area.x, list_area.y + row as u16, list_area.width, 1);
+        app.click_map.add(row_area, Target::ListRow(idx));
+    }
 }
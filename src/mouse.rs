@@ -0,0 +1,60 @@
+use crate::app::CurrentlyEditing;
+use ratatui::{
+    crossterm::event::KeyCode,
+    layout::{Position, Rect},
+};
+use std::cell::RefCell;
+use std::time::{Duration, Instant};
+
+// two clicks on the same row within this time are a double click
+pub const DOUBLE_CLICK: Duration = Duration::from_millis(400);
+
+// what a click on a region of the screen does
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Target {
+    ListRow(usize),          // a profile in the main list
+    Key(KeyCode),            // a button standing for the key in its label
+    Field(CurrentlyEditing), // an input of the Editing popup
+}
+
+/*
+* The clickable regions of the last drawn frame. Rendering only gets a shared
+* reference to the app, so regions are collected through a RefCell. Later
+* regions are drawn on top of earlier ones and win when they overlap.
+*/
+#[derive(Default)]
+pub struct ClickMap {
+    regions: RefCell<Vec<(Rect, Target)>>,
+}
+
+impl ClickMap {
+    pub fn clear(&self) {
+        self.regions.borrow_mut().clear();
+    }
+
+    pub fn add(&self, area: Rect, target: Target) {
+        self.regions.borrow_mut().push((area, target));
+    }
+
+    pub fn hit(&self, column: u16, row: u16) -> Option<Target> {
+        self.regions
+            .borrow()
+            .iter()
+            .rev()
+            .find(|(area, _)| area.contains(Position::new(column, row)))
+            .map(|(_, target)| *target)
+    }
+}
+
+// when and where the last click landed, to tell double clicks apart
+#[derive(Clone, Copy)]
+pub struct LastClick {
+    pub at: Instant,
+    pub row: usize,
+}
+
+impl LastClick {
+    pub fn is_double(&self, row: usize, now: Instant) -> bool {
+        self.row == row && now.duration_since(self.at) <= DOUBLE_CLICK
+    }
+}
@@ -0,0 +1,117 @@
+use crate::app::Entry;
+use crate::git::GitRunner;
+use crate::inject::git_path;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+// first line after the shebang of hooks written by gat, other hooks are never touched
+const HOOK_MARKER: &str = "# installed by gat for co-author trailers";
+const HOOK_NAME: &str = "prepare-commit-msg";
+// trailers of the running pairing session, one per line, inside the git dir
+const SESSION_NAME: &str = "gat-coauthors";
+
+/*
+* The hook appends every trailer of the session file to the commit message.
+* It does not need gat, so commits from IDEs and other tools get them too.
+*/
+const HOOK: &str = r#"#!/bin/sh
+# installed by gat for co-author trailers
+session="$(git rev-parse --git-path gat-coauthors)"
+[ -s "$session" ] || exit 0
+while IFS= read -r trailer; do
+  [ -n "$trailer" ] && git interpret-trailers --in-place --if-exists addIfDifferent --trailer "$trailer" "$1"
+done < "$session"
+exit 0
+"#;
+
+pub fn trailer(entry: &Entry) -> String {
+    format!("Co-authored-by: {} <{}>", entry.author_name(), entry.email)
+}
+
+pub fn session_file(git: &dyn GitRunner, workdir: &Path) -> Result<PathBuf, String> {
+    git_path(git, workdir, SESSION_NAME)
+}
+
+// the trailers of the running session, empty if there is none
+pub fn load_session(git: &dyn GitRunner, workdir: &Path) -> Vec<String> {
+    session_file(git, workdir)
+        .ok()
+        .and_then(|file| fs::read_to_string(file).ok())
+        .map(|content| {
+            content
+                .lines()
+                .filter(|line| !line.is_empty())
+                .map(|line| line.to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn hook_file(git: &dyn GitRunner, workdir: &Path) -> Result<PathBuf, String> {
+    git_path(git, workdir, &format!("hooks/{}", HOOK_NAME))
+}
+
+fn is_ours(hook: &Path) -> bool {
+    fs::read_to_string(hook).is_ok_and(|content| content.lines().nth(1) == Some(HOOK_MARKER))
+}
+
+#[cfg(unix)]
+fn make_executable(path: &Path) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o755))
+}
+
+#[cfg(not(unix))]
+fn make_executable(_path: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+/*
+* Start or update the pairing session of the repo with the given co-authors and
+* install the hook. An existing prepare-commit-msg hook of someone else is left
+* alone and the session is not started.
+*/
+pub fn start(git: &dyn GitRunner, workdir: &Path, coauthors: &[&Entry]) -> Result<(), String> {
+    let hook = hook_file(git, workdir)?;
+    if hook.exists() && !is_ours(&hook) {
+        return Err(format!(
+            "{} already exists, add the trailers of {} to it yourself",
+            hook.display(),
+            session_file(git, workdir)?.display()
+        ));
+    }
+    let io_err = |path: &Path| {
+        let path = path.display().to_string();
+        move |err: io::Error| format!("{}: {}", path, err)
+    };
+    let session = session_file(git, workdir)?;
+    let content: String = coauthors
+        .iter()
+        .map(|entry| format!("{}\n", trailer(entry)))
+        .collect();
+    fs::write(&session, content).map_err(io_err(&session))?;
+    if let Some(dir) = hook.parent() {
+        fs::create_dir_all(dir).map_err(io_err(dir))?;
+    }
+    fs::write(&hook, HOOK).map_err(io_err(&hook))?;
+    make_executable(&hook).map_err(io_err(&hook))
+}
+
+// end the session, removing the session file and the hook if gat installed it
+pub fn end(git: &dyn GitRunner, workdir: &Path) -> Result<(), String> {
+    let mut files = vec![session_file(git, workdir)?];
+    let hook = hook_file(git, workdir)?;
+    if is_ours(&hook) {
+        files.push(hook);
+    }
+    for file in files {
+        match fs::remove_file(&file) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => {
+                return Err(format!("{}: {}", file.display(), err))
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
@@ -1,34 +1,232 @@
-use crate::ui::fixed_size_centered_rect;
+use crate::ui::{fixed_size_centered_rect, MAIN_KEYS};
 use ratatui::{
-    layout::{Constraint, Direction, Layout},
+    crossterm::event::KeyCode,
+    layout::{Constraint, Direction, Layout, Margin, Rect},
     style::{Color, Modifier, Style, Stylize},
-    text::Text,
-    widgets::{Block, BorderType, Borders, Paragraph, Wrap},
+    text::{Line, Span, Text},
+    widgets::{
+        Block, BorderType, Borders, Clear, Gauge, List, ListItem, ListState, Paragraph, Wrap,
+    },
     Frame,
 };
 
 use crate::app::{App, CurrentScreen, CurrentlyEditing};
+use crate::bundle;
+use crate::inject::InjectScope;
+use crate::input::TextInput;
+use crate::mouse::Target;
+use crate::trash;
 
-fn render_injecting_popup(frame: &mut Frame) {
+/*
+* Ask a y/n question about changing the repo config and list the keys the
+* answer will change in which file, based on app.injection_plan.
+*/
+fn render_config_changes_popup(frame: &mut Frame, app: &App, question: &str) {
     let popup_block = Block::default()
         .title("y/n")
         .borders(Borders::ALL)
         .style(Style::default().bg(Color::DarkGray));
 
-    let exit_text = Text::styled(
-        "Do you want to use this profile in the current repo?",
-        Style::default(),
-    )
-    .add_modifier(Modifier::BOLD);
-    let exit_paragraph = Paragraph::new(exit_text)
+    let mut lines = vec![
+        Line::from(Span::styled(
+            question.to_string(),
+            Style::default().add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+    ];
+    lines.push(scope_line(app));
+    lines.push(Line::from(""));
+    match &app.injection_plan {
+        Ok(changes) => {
+            let mut current_file = None;
+            for change in changes {
+                if current_file != Some(&change.file) {
+                    lines.push(Line::from(Span::styled(
+                        change.file.display().to_string(),
+                        Style::default().fg(Color::Yellow),
+                    )));
+                    current_file = Some(&change.file);
+                }
+                lines.push(Line::from(format!("  {} = {}", change.key, change.value)));
+            }
+        }
+        Err(err) => lines.push(Line::from(Span::styled(
+            err.clone(),
+            Style::default().fg(Color::Red),
+        ))),
+    }
+
+    let height = lines.len() as u16 + 2;
+    let changes_paragraph = Paragraph::new(lines)
         .block(popup_block)
         .wrap(Wrap { trim: false });
 
-    let area = fixed_size_centered_rect(60, 3, frame.area());
-    frame.render_widget(exit_paragraph, area);
+    let area = fixed_size_centered_rect(80, height, frame.area());
+    frame.render_widget(Clear, area);
+    frame.render_widget(changes_paragraph, area);
+    render_buttons(frame, app, area, &YES_NO);
+}
+
+/*
+* Clickable buttons on the bottom border of a popup, each standing for the key
+* in front of its label, e.g. "y" for "y: Yes".
+*/
+fn render_buttons(frame: &mut Frame, app: &App, area: Rect, buttons: &[(KeyCode, &str)]) {
+    let mut x = area.x + 2;
+    let y = area.bottom().saturating_sub(1);
+    for (key, label) in buttons {
+        let text = format!(" {} ", label);
+        let width = (text.chars().count() as u16).min(area.right().saturating_sub(x));
+        let button = Rect::new(x, y, width, 1);
+        frame.render_widget(
+            Paragraph::new(text).style(Style::default().fg(Color::Black).bg(Color::White)),
+            button,
+        );
+        app.click_map.add(button, Target::Key(*key));
+        x += width + 1;
+    }
+}
+
+const YES_NO: [(KeyCode, &str); 2] = [
+    (KeyCode::Char('y'), "y: Yes"),
+    (KeyCode::Char('n'), "n: No"),
+];
+
+// the scope options of the Injecting and Removing popups with the chosen one highlighted
+fn scope_line<'a>(app: &App) -> Line<'a> {
+    let mut scopes = vec![Span::raw("Scope (Tab): ")];
+    for scope in InjectScope::ALL {
+        let style = if scope == app.inject_scope {
+            Style::default().fg(Color::Black).bg(Color::LightYellow)
+        } else {
+            Style::default()
+        };
+        scopes.push(Span::styled(format!(" {} ", scope.label()), style));
+    }
+    Line::from(scopes)
+}
+
+fn render_removing_popup(frame: &mut Frame, app: &App) {
+    let popup_block = Block::default()
+        .title("y/n")
+        .borders(Borders::ALL)
+        .style(Style::default().bg(Color::DarkGray));
+
+    let mut lines = vec![
+        Line::from(Span::styled(
+            format!(
+                "Do you want to remove every key gat set from {}?",
+                app.target_label()
+            ),
+            Style::default().add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+        scope_line(app),
+        Line::from(""),
+    ];
+    match &app.uninject_plan {
+        Ok(restores) if restores.is_empty() => {
+            lines.push(Line::from("gat has not set anything in this scope"))
+        }
+        Ok(restores) => {
+            let mut current_file = None;
+            for restore in restores {
+                if current_file != Some(&restore.file) {
+                    lines.push(Line::from(Span::styled(
+                        restore.file.display().to_string(),
+                        Style::default().fg(Color::Yellow),
+                    )));
+                    current_file = Some(&restore.file);
+                }
+                let action = match (&restore.previous, restore.modified) {
+                    (_, true) => "changed since, kept as is".to_string(),
+                    (Some(previous), false) => format!("restored to {}", previous),
+                    (None, false) => "unset".to_string(),
+                };
+                lines.push(Line::from(format!("  {} {}", restore.key, action)));
+            }
+        }
+        Err(err) => lines.push(Line::from(Span::styled(
+            err.clone(),
+            Style::default().fg(Color::Red),
+        ))),
+    }
+
+    let height = lines.len() as u16 + 2;
+    let restore_paragraph = Paragraph::new(lines)
+        .block(popup_block)
+        .wrap(Wrap { trim: false });
+
+    let area = fixed_size_centered_rect(80, height, frame.area());
+    frame.render_widget(Clear, area);
+    frame.render_widget(restore_paragraph, area);
+    render_buttons(frame, app, area, &YES_NO);
+}
+
+fn render_config_editor_popup(frame: &mut Frame, app: &App) {
+    let Some(idx) = app.selected_index else {
+        return;
+    };
+    let entry = &app.entries[idx];
+    let popup_block = Block::default()
+        .title(format!("Git config of {}", entry.alias))
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(Color::White))
+        .style(Style::default().bg(Color::DarkGray));
+
+    let area = fixed_size_centered_rect(70, 18, frame.area());
+    frame.render_widget(Clear, area);
+    frame.render_widget(popup_block, area);
+
+    let popup_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([
+            Constraint::Min(1),
+            Constraint::Length(3),
+            Constraint::Length(3),
+        ])
+        .split(area);
+
+    let mut list_items = Vec::<ListItem>::new();
+    for (key, value) in &entry.git_config {
+        list_items.push(ListItem::new(Line::from(vec![
+            Span::styled(key.clone(), Style::default().fg(Color::Yellow)),
+            Span::raw(format!(" = {}", value)),
+        ])));
+    }
+    if list_items.is_empty() {
+        list_items.push(ListItem::new("No extra git config keys yet"));
+    }
+    let mut list_state = ListState::default().with_selected(app.config_selected);
+    let list = List::new(list_items).highlight_symbol(">>");
+    frame.render_stateful_widget(list, popup_chunks[0], &mut list_state);
+
+    let active_style = Style::default().bg(Color::LightYellow).fg(Color::Black);
+    let fields = [
+        (
+            CurrentlyEditing::ConfigKey,
+            "Key (e.g. pull.rebase)",
+            &app.config_key_input,
+        ),
+        (
+            CurrentlyEditing::ConfigValue,
+            "Value",
+            &app.config_value_input,
+        ),
+    ];
+    for (idx, (field, title, input)) in fields.into_iter().enumerate() {
+        let focused = app.currently_editing.as_ref() == Some(&field);
+        let mut block = Block::default().title(title).borders(Borders::ALL);
+        if focused {
+            block = block.style(active_style);
+        }
+        input.render(frame, popup_chunks[idx + 1], block, focused);
+    }
 }
 
-fn render_deleting_popup(frame: &mut Frame) {
+fn render_deleting_popup(frame: &mut Frame, app: &App) {
     let popup_block = Block::default()
         .title("y/n")
         .borders(Borders::ALL)
@@ -45,9 +243,196 @@ fn render_deleting_popup(frame: &mut Frame) {
 
     let area = fixed_size_centered_rect(60, 3, frame.area());
     frame.render_widget(exit_paragraph, area);
+    render_buttons(frame, app, area, &YES_NO);
+}
+
+// every key of the main screen in two columns
+fn render_help_popup(frame: &mut Frame) {
+    let popup_block = Block::default()
+        .title("Keys")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(Color::White))
+        .style(Style::default().bg(Color::DarkGray));
+
+    let rows = MAIN_KEYS.len().div_ceil(2);
+    let area = fixed_size_centered_rect(80, rows as u16 + 2, frame.area());
+    frame.render_widget(Clear, area);
+    frame.render_widget(popup_block, area);
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(area.inner(Margin::new(1, 1)));
+    for (column, keys) in columns.iter().zip(MAIN_KEYS.chunks(rows)) {
+        let lines: Vec<Line> = keys
+            .iter()
+            .map(|(key, action)| {
+                Line::from(vec![
+                    Span::styled(format!("{:>6} ", key), Style::default().fg(Color::Yellow)),
+                    Span::raw(*action),
+                ])
+            })
+            .collect();
+        frame.render_widget(Paragraph::new(lines), *column);
+    }
+}
+
+fn render_rotating_popup(frame: &mut Frame, app: &App) {
+    let Some(idx) = app.selected_index else {
+        return;
+    };
+    let popup_block = Block::default()
+        .title(format!("Rotate the token of {}", app.entries[idx].alias))
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(Color::White))
+        .style(Style::default().bg(Color::DarkGray));
+
+    if !app.rotation_summary.is_empty() {
+        let lines: Vec<Line> = app
+            .rotation_summary
+            .iter()
+            .map(|line| Line::from(line.as_str()))
+            .collect();
+        let height = lines.len() as u16 + 2;
+        let summary_paragraph = Paragraph::new(lines)
+            .block(popup_block)
+            .wrap(Wrap { trim: false });
+        let area = fixed_size_centered_rect(80, height, frame.area());
+        frame.render_widget(Clear, area);
+        frame.render_widget(summary_paragraph, area);
+        return;
+    }
+
+    let area = fixed_size_centered_rect(60, 6, frame.area());
+    frame.render_widget(Clear, area);
+    frame.render_widget(popup_block, area);
+
+    let popup_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([Constraint::Length(3), Constraint::Length(1)])
+        .split(area);
+
+    let token_block = Block::default().title("New PA-Token").borders(Borders::ALL);
+    app.new_token_input
+        .render(frame, popup_chunks[0], token_block, true);
+    let host = &app.entries[idx].host;
+    let validation = match (app.rotate_validate, host.is_empty()) {
+        (false, _) => "The token is used without checking it".to_string(),
+        (true, true) => "Checking the token needs a host in the profile".to_string(),
+        (true, false) => format!("The token is checked with {} first", host),
+    };
+    frame.render_widget(
+        Paragraph::new(Span::styled(validation, Style::default().fg(Color::Yellow))),
+        popup_chunks[1],
+    );
+}
+
+fn render_scrubbing_popup(frame: &mut Frame, app: &App) {
+    let title = if app.scrub_findings.is_empty() {
+        "Esc"
+    } else {
+        "y/n"
+    };
+    let popup_block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .style(Style::default().bg(Color::DarkGray));
+
+    let mut lines = vec![Line::from(Span::styled(
+        "Do you want to remove the tokens from these remote urls?",
+        Style::default().add_modifier(Modifier::BOLD),
+    ))];
+    let helper = if app.scrub_helper {
+        "gat provides the token to git from now on"
+    } else {
+        "git will ask for the token"
+    };
+    lines.push(Line::from(vec![
+        Span::raw("Afterwards: "),
+        Span::styled(helper, Style::default().fg(Color::Yellow)),
+    ]));
+    lines.push(Line::from(""));
+    if app.scrub_findings.is_empty() {
+        lines.push(Line::from(
+            "No remote with a token in the current repo or the scanned repos",
+        ));
+    }
+    let mut current_repo = None;
+    for finding in &app.scrub_findings {
+        if current_repo != Some(&finding.repo) {
+            lines.push(Line::from(Span::styled(
+                finding.repo.display().to_string(),
+                Style::default().fg(Color::Yellow),
+            )));
+            current_repo = Some(&finding.repo);
+        }
+        let owner = match &finding.profile {
+            Some(alias) => format!("token of {}", alias),
+            None => "unknown token".to_string(),
+        };
+        lines.push(Line::from(format!(
+            "  {} {} ({})",
+            finding.key,
+            finding.redacted_url(),
+            owner
+        )));
+    }
+
+    let height = lines.len() as u16 + 2;
+    let scrub_paragraph = Paragraph::new(lines)
+        .block(popup_block)
+        .wrap(Wrap { trim: false });
+
+    let area = fixed_size_centered_rect(80, height, frame.area());
+    frame.render_widget(Clear, area);
+    frame.render_widget(scrub_paragraph, area);
+    if !app.scrub_findings.is_empty() {
+        render_buttons(frame, app, area, &YES_NO);
+    }
+}
+
+fn render_propagating_popup(frame: &mut Frame, app: &App) {
+    let Some(idx) = app.selected_index else {
+        return;
+    };
+    let popup_block = Block::default()
+        .title("y/n")
+        .borders(Borders::ALL)
+        .style(Style::default().bg(Color::DarkGray));
+
+    let repos = app.repos_of(idx);
+    let mut lines = vec![
+        Line::from(Span::styled(
+            format!(
+                "The identity changed, update it in the {} repos using this profile?",
+                repos.len()
+            ),
+            Style::default().add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+    ];
+    for record in repos {
+        lines.push(Line::from(format!(
+            "  {} ({})",
+            record.path.display(),
+            record.scope.label()
+        )));
+    }
+
+    let height = lines.len() as u16 + 2;
+    let propagate_paragraph = Paragraph::new(lines)
+        .block(popup_block)
+        .wrap(Wrap { trim: false });
+
+    let area = fixed_size_centered_rect(80, height, frame.area());
+    frame.render_widget(Clear, area);
+    frame.render_widget(propagate_paragraph, area);
+    render_buttons(frame, app, area, &YES_NO);
 }
 
-fn render_cloning_popup(frame: &mut Frame, clone_url: String) {
+fn render_cloning_popup(frame: &mut Frame, clone_url: &TextInput) {
     let popup_block = Block::default()
         .title("Clone using selected profile")
         .borders(Borders::ALL)
@@ -71,18 +456,498 @@ fn render_cloning_popup(frame: &mut Frame, clone_url: String) {
     let url_block = Block::default()
         .title("Paste url (Github: green clone button)")
         .borders(Borders::ALL);
-    let url_text = Paragraph::new(clone_url).block(url_block);
-    frame.render_widget(url_text, popup_chunks[1]);
+    clone_url.render(frame, popup_chunks[1], url_block, true);
+}
+
+fn render_audit_log_popup(frame: &mut Frame, app: &App) {
+    let popup_block = Block::default()
+        .title(format!("Audit log {}", app.audit_file.display()))
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(Color::White))
+        .style(Style::default().bg(Color::DarkGray));
+
+    let area = frame.area().inner(Margin::new(2, 2));
+    frame.render_widget(Clear, area);
+    frame.render_widget(popup_block, area);
+
+    let popup_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([Constraint::Length(3), Constraint::Min(1)])
+        .split(area);
+
+    let events = app.filtered_audit();
+    let filter_block = Block::default()
+        .title(format!(
+            "Filter ({} of {} events, times in UTC)",
+            events.len(),
+            app.audit_events.len()
+        ))
+        .borders(Borders::ALL);
+    app.audit_filter_input
+        .render(frame, popup_chunks[0], filter_block, true);
+
+    let list_items: Vec<ListItem> = events
+        .iter()
+        .map(|event| ListItem::new(event.describe()))
+        .collect();
+    let mut list_state = ListState::default().with_selected(app.audit_selected);
+    let list = List::new(list_items).highlight_symbol(">>");
+    frame.render_stateful_widget(list, popup_chunks[1], &mut list_state);
+}
+
+fn render_pairing_popup(frame: &mut Frame, app: &App) {
+    let popup_block = Block::default()
+        .title("Co-authors of commits in this repo")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(Color::White))
+        .style(Style::default().bg(Color::DarkGray));
+
+    let area = frame.area().inner(Margin::new(6, 3));
+    frame.render_widget(Clear, area);
+    frame.render_widget(popup_block, area);
+
+    let popup_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([Constraint::Length(2), Constraint::Min(1)])
+        .split(area);
+
+    let session = if app.coauthors.is_empty() {
+        "No pairing session, commits have a single author".to_string()
+    } else {
+        format!("Pairing with {} co-authors", app.coauthors.len())
+    };
+    frame.render_widget(
+        Paragraph::new(Span::styled(session, Style::default().fg(Color::Yellow))),
+        popup_chunks[0],
+    );
+
+    let mut list_items = Vec::<ListItem>::new();
+    for entry in &app.entries {
+        let chosen = app.coauthor_choice.contains(&entry.alias);
+        list_items.push(ListItem::new(Line::from(vec![
+            Span::raw(if chosen { "[x] " } else { "[ ] " }),
+            Span::styled(entry.alias.clone(), Style::default().fg(Color::Yellow)),
+            Span::raw(format!("  {} <{}>", entry.author_name(), entry.email)),
+        ])));
+    }
+    let mut list_state = ListState::default().with_selected(app.pairing_selected);
+    let list = List::new(list_items).highlight_symbol(">>");
+    frame.render_stateful_widget(list, popup_chunks[1], &mut list_state);
+}
+
+fn render_choosing_template_popup(frame: &mut Frame, app: &App) {
+    let popup_block = Block::default()
+        .title("Start the new profile from")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(Color::White))
+        .style(Style::default().bg(Color::DarkGray));
+
+    let shown = app.templates.len().min(12) as u16 + 1;
+    let area = fixed_size_centered_rect(70, 2 + shown, frame.area());
+    frame.render_widget(Clear, area);
+    frame.render_widget(popup_block, area);
+
+    let mut list_items = vec![ListItem::new(Span::raw("Blank profile"))];
+    for template in &app.templates {
+        list_items.push(ListItem::new(Line::from(vec![
+            Span::styled(template.name.clone(), Style::default().fg(Color::Yellow)),
+            Span::raw(format!("  {}", template.describe())),
+        ])));
+    }
+    let mut list_state = ListState::default().with_selected(app.template_selected);
+    let list = List::new(list_items).highlight_symbol(">>");
+    frame.render_stateful_widget(list, area.inner(Margin::new(1, 1)), &mut list_state);
+}
+
+fn render_choosing_repo_popup(frame: &mut Frame, app: &App) {
+    let popup_block = Block::default()
+        .title("Work on another repo")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(Color::White))
+        .style(Style::default().bg(Color::DarkGray));
+
+    let shown = app.repo_completions.len().min(8) as u16;
+    let area = fixed_size_centered_rect(70, 5 + shown, frame.area());
+    frame.render_widget(Clear, area);
+    frame.render_widget(popup_block, area);
+
+    let popup_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([Constraint::Length(3), Constraint::Min(0)])
+        .split(area);
+
+    let path_block = Block::default().title("Path").borders(Borders::ALL);
+    app.repo_path_input
+        .render(frame, popup_chunks[0], path_block, true);
+    let completions: Vec<Line> = app
+        .repo_completions
+        .iter()
+        .map(|name| {
+            Line::from(Span::styled(
+                format!("{}/", name),
+                Style::default().fg(Color::Gray),
+            ))
+        })
+        .collect();
+    frame.render_widget(Paragraph::new(completions), popup_chunks[1]);
+}
+
+fn render_quitting_popup(frame: &mut Frame, app: &App) {
+    let popup_block = Block::default()
+        .title("s/d")
+        .borders(Borders::ALL)
+        .style(Style::default().bg(Color::DarkGray));
+
+    let quit_text = Text::styled(
+        "You have unsaved changes. Save them before quitting (s) or discard them (d)?",
+        Style::default(),
+    )
+    .add_modifier(Modifier::BOLD);
+    let quit_paragraph = Paragraph::new(quit_text)
+        .block(popup_block)
+        .wrap(Wrap { trim: false });
+
+    let area = fixed_size_centered_rect(60, 4, frame.area());
+    frame.render_widget(quit_paragraph, area);
+    render_buttons(
+        frame,
+        app,
+        area,
+        &[
+            (KeyCode::Char('s'), "s: Save"),
+            (KeyCode::Char('d'), "d: Discard"),
+            (KeyCode::Esc, "Esc: Back"),
+        ],
+    );
+}
+
+fn render_conflict_popup(frame: &mut Frame, app: &App) {
+    let popup_block = Block::default()
+        .title("m/r")
+        .borders(Borders::ALL)
+        .style(Style::default().bg(Color::DarkGray));
+
+    let conflict_text = Text::styled(
+        "The profile store was changed by another program since gat loaded it. \
+         Merge your changes into it (m) or discard them and reload (r)?",
+        Style::default(),
+    )
+    .add_modifier(Modifier::BOLD);
+    let conflict_paragraph = Paragraph::new(conflict_text)
+        .block(popup_block)
+        .wrap(Wrap { trim: false });
+
+    let area = fixed_size_centered_rect(60, 5, frame.area());
+    frame.render_widget(conflict_paragraph, area);
+    render_buttons(
+        frame,
+        app,
+        area,
+        &[
+            (KeyCode::Char('m'), "m: Merge"),
+            (KeyCode::Char('r'), "r: Reload"),
+        ],
+    );
+}
+
+fn render_trash_popup(frame: &mut Frame, app: &App) {
+    let popup_block = Block::default()
+        .title(format!(
+            "Trash (profiles are purged after {} days)",
+            trash::retention_days()
+        ))
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(Color::White))
+        .style(Style::default().bg(Color::DarkGray));
+
+    let mut list_items = Vec::<ListItem>::new();
+    for trashed in &app.trash {
+        list_items.push(ListItem::new(Line::from(vec![
+            Span::styled(
+                trashed.entry.alias.clone(),
+                Style::default().fg(Color::Yellow),
+            ),
+            Span::styled(
+                format!(" (deleted {} days ago)", trashed.days_in_trash()),
+                Style::default().fg(Color::Gray),
+            ),
+        ])));
+    }
+    if list_items.is_empty() {
+        list_items.push(ListItem::new("The trash is empty"));
+    }
+
+    let area = fixed_size_centered_rect(60, 12, frame.area());
+    let mut list_state = ListState::default().with_selected(app.trash_selected);
+    let list = List::new(list_items)
+        .block(popup_block)
+        .highlight_symbol(">>");
+    frame.render_widget(Clear, area);
+    frame.render_stateful_widget(list, area, &mut list_state);
+}
+
+fn render_scanning_popup(frame: &mut Frame, app: &App) {
+    let popup_block = Block::default()
+        .title("Scan for repositories")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(Color::White))
+        .style(Style::default().bg(Color::DarkGray));
+
+    let area = frame.area().inner(Margin::new(2, 3));
+    frame.render_widget(Clear, area);
+    frame.render_widget(popup_block, area);
+
+    let popup_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([Constraint::Length(3), Constraint::Min(1)])
+        .split(area);
+
+    let Some((done, total)) = app.scan_progress else {
+        let root_block = Block::default()
+            .title("Directory to scan (Enter to start)")
+            .borders(Borders::ALL);
+        app.scan_root_input
+            .render(frame, popup_chunks[0], root_block, true);
+        return;
+    };
+
+    let ratio = if total == 0 {
+        if app.scan_receiver.is_some() {
+            0.0
+        } else {
+            1.0
+        }
+    } else {
+        done as f64 / total as f64
+    };
+    let label = if app.scan_receiver.is_some() {
+        format!(
+            "{} repos found, scanning {}/{}",
+            app.scan_results.len(),
+            done,
+            total
+        )
+    } else {
+        format!("{} repos found", app.scan_results.len())
+    };
+    let gauge = Gauge::default()
+        .block(
+            Block::default()
+                .title(app.scan_root_input.value().to_string())
+                .borders(Borders::ALL),
+        )
+        .gauge_style(Style::default().fg(Color::Green))
+        .ratio(ratio)
+        .label(label);
+    frame.render_widget(gauge, popup_chunks[0]);
+
+    let mut list_items = Vec::<ListItem>::new();
+    for repo in &app.scan_results {
+        let identity = match (&repo.name, &repo.email) {
+            (None, None) => "no identity".to_string(),
+            (name, email) => format!(
+                "{} <{}>",
+                name.as_deref().unwrap_or("?"),
+                email.as_deref().unwrap_or("?")
+            ),
+        };
+        let profile = match &repo.profile {
+            Some(alias) if app.entries.iter().any(|entry| entry.alias == *alias) => {
+                format!(" -> {}", alias)
+            }
+            Some(alias) => format!(" -> {} (deleted)", alias),
+            None => String::new(),
+        };
+        list_items.push(ListItem::new(Line::from(vec![
+            Span::raw(if repo.selected { "[x] " } else { "[ ] " }),
+            Span::styled(
+                repo.path.display().to_string(),
+                Style::default().fg(Color::Yellow),
+            ),
+            Span::raw(format!(
+                "  {}  {}",
+                identity,
+                repo.remote_host.as_deref().unwrap_or("no remote")
+            )),
+            Span::styled(profile, Style::default().fg(Color::Green)),
+        ])));
+    }
+    let mut list_state = ListState::default().with_selected(app.scan_selected);
+    let list = List::new(list_items).highlight_symbol(">>");
+    frame.render_stateful_widget(list, popup_chunks[1], &mut list_state);
+}
+
+fn render_bundle_popup(frame: &mut Frame, app: &App) {
+    let popup_block = Block::default()
+        .title("Import a bundle exported with gat export")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(Color::White))
+        .style(Style::default().bg(Color::DarkGray));
+
+    let area = frame.area().inner(Margin::new(2, 3));
+    frame.render_widget(Clear, area);
+    frame.render_widget(popup_block, area);
+
+    let popup_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Length(3),
+            Constraint::Min(1),
+        ])
+        .split(area);
+
+    let path_block = Block::default().title("Bundle file").borders(Borders::ALL);
+    let reading = app.bundle_incoming.is_none();
+    app.bundle_path_input.render(
+        frame,
+        popup_chunks[0],
+        path_block,
+        reading && !app.bundle_asks_passphrase,
+    );
+    if app.bundle_asks_passphrase {
+        let passphrase_block = Block::default()
+            .title("Passphrase the tokens were encrypted with")
+            .borders(Borders::ALL);
+        app.bundle_passphrase_input
+            .render(frame, popup_chunks[1], passphrase_block, reading);
+    }
+
+    let Some(incoming) = &app.bundle_incoming else {
+        return;
+    };
+    let mut list_items = Vec::<ListItem>::new();
+    for profile in incoming {
+        let (action, color) = match (profile.conflict, profile.resolution) {
+            (false, bundle::Resolution::Skip) => ("already here".to_string(), Color::Gray),
+            (false, _) => ("new".to_string(), Color::Green),
+            (true, resolution) => (format!("conflict: {}", resolution.label()), Color::Red),
+        };
+        list_items.push(ListItem::new(Line::from(vec![
+            Span::styled(
+                profile.entry.alias.clone(),
+                Style::default().fg(Color::Yellow),
+            ),
+            Span::raw(format!(
+                "  {} <{}>  ",
+                profile.entry.author_name(),
+                profile.entry.email
+            )),
+            Span::styled(action, Style::default().fg(color)),
+        ])));
+    }
+    let mut list_state = ListState::default().with_selected(app.bundle_selected);
+    let list = List::new(list_items).highlight_symbol(">>");
+    frame.render_stateful_widget(list, popup_chunks[2], &mut list_state);
+}
+
+fn render_importing_popup(frame: &mut Frame, app: &App) {
+    let popup_block = Block::default()
+        .title("Import existing profiles")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(Color::White))
+        .style(Style::default().bg(Color::DarkGray));
+
+    let area = frame.area().inner(Margin::new(2, 3));
+    frame.render_widget(Clear, area);
+    frame.render_widget(popup_block, area);
+
+    let popup_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([Constraint::Length(3), Constraint::Min(1)])
+        .split(area);
+
+    let Some(candidates) = &app.import_candidates else {
+        let root_block = Block::default()
+            .title("Also look at repos below (empty to skip, Enter to search)")
+            .borders(Borders::ALL);
+        app.import_root_input
+            .render(frame, popup_chunks[0], root_block, true);
+        return;
+    };
+
+    let found = Paragraph::new(format!(
+        "{} new profiles found in ~/.gitconfig, its includes, ~/.git-credentials, ~/.ssh/config and repos",
+        candidates.len()
+    ))
+    .block(Block::default().borders(Borders::ALL))
+    .wrap(Wrap { trim: true });
+    frame.render_widget(found, popup_chunks[0]);
+
+    let mut list_items = Vec::<ListItem>::new();
+    for candidate in candidates {
+        let entry = &candidate.entry;
+        let mut details = Vec::new();
+        if !entry.email.is_empty() {
+            details.push(format!("{} <{}>", entry.author_name(), entry.email));
+        } else if !entry.username.is_empty() {
+            details.push(entry.username.clone());
+        }
+        if !entry.host.is_empty() {
+            details.push(entry.host.clone());
+        }
+        if !entry.pa_token.is_empty() {
+            details.push("token".to_string());
+        }
+        list_items.push(ListItem::new(vec![
+            Line::from(vec![
+                Span::raw(if candidate.selected { "[x] " } else { "[ ] " }),
+                Span::styled(entry.alias.clone(), Style::default().fg(Color::Yellow)),
+                Span::raw(format!("  {}", details.join(", "))),
+            ]),
+            Line::from(Span::styled(
+                format!("      from {}", candidate.sources.join(", ")),
+                Style::default().fg(Color::Gray),
+            )),
+        ]));
+    }
+    let mut list_state = ListState::default().with_selected(app.import_selected);
+    let list = List::new(list_items).highlight_symbol(">>");
+    frame.render_stateful_widget(list, popup_chunks[1], &mut list_state);
 }
 
 pub fn render_active_popups(frame: &mut Frame, app: &App) {
-    let _ = match app.current_screen {
-        CurrentScreen::Cloning => render_cloning_popup(frame, app.clone_url_input.clone()),
-        CurrentScreen::Deleting => render_deleting_popup(frame),
-        CurrentScreen::Editing => render_editing_popup(frame, &app),
-        CurrentScreen::Injecting => render_injecting_popup(frame),
+    match app.current_screen {
+        CurrentScreen::Cloning => render_cloning_popup(frame, &app.clone_url_input),
+        CurrentScreen::Deleting => render_deleting_popup(frame, app),
+        CurrentScreen::Editing => render_editing_popup(frame, app),
+        CurrentScreen::Injecting => render_config_changes_popup(
+            frame,
+            app,
+            &format!("Do you want to use this profile in {}?", app.target_label()),
+        ),
+        CurrentScreen::Removing => render_removing_popup(frame, app),
+        CurrentScreen::ConfigEditor => render_config_editor_popup(frame, app),
+        CurrentScreen::Scanning => render_scanning_popup(frame, app),
+        CurrentScreen::Trash => render_trash_popup(frame, app),
+        CurrentScreen::Conflict => render_conflict_popup(frame, app),
+        CurrentScreen::Quitting => render_quitting_popup(frame, app),
+        CurrentScreen::Propagating => render_propagating_popup(frame, app),
+        CurrentScreen::Scrubbing => render_scrubbing_popup(frame, app),
+        CurrentScreen::Rotating => render_rotating_popup(frame, app),
+        CurrentScreen::Importing => render_importing_popup(frame, app),
+        CurrentScreen::Bundle => render_bundle_popup(frame, app),
+        CurrentScreen::ChoosingRepo => render_choosing_repo_popup(frame, app),
+        CurrentScreen::Pairing => render_pairing_popup(frame, app),
+        CurrentScreen::ChoosingTemplate => render_choosing_template_popup(frame, app),
+        CurrentScreen::AuditLog => render_audit_log_popup(frame, app),
+        CurrentScreen::Help => render_help_popup(frame),
         _ => {}
-    };
+    }
 }
 
 fn render_editing_popup(frame: &mut Frame, app: &App) {
@@ -96,49 +961,63 @@ fn render_editing_popup(frame: &mut Frame, app: &App) {
         .border_style(Style::default().fg(Color::White))
         .style(Style::default().bg(Color::DarkGray));
 
-    let area = fixed_size_centered_rect(60, 18, frame.area());
+    let area = fixed_size_centered_rect(60, 23, frame.area());
+    frame.render_widget(Clear, area);
     frame.render_widget(popup_block, area);
 
     let popup_chunks = Layout::default()
         .direction(Direction::Vertical)
         .margin(1)
-        .constraints([
-            Constraint::Percentage(20),
-            Constraint::Percentage(20),
-            Constraint::Percentage(20),
-            Constraint::Percentage(20),
-            Constraint::Percentage(20),
-        ])
+        .constraints([Constraint::Length(3); 7])
         .split(area);
 
-    let mut alias_block = Block::default()
+    let alias_block = Block::default()
         .title("Profile Alias (not visible in git)")
         .borders(Borders::ALL);
-    let mut username_block = Block::default().title("Username").borders(Borders::ALL);
-    let mut email_block = Block::default().title("Email").borders(Borders::ALL);
-    let mut token_block = Block::default().title("PA-Token").borders(Borders::ALL);
-    let mut nickname_block = Block::default()
+    let username_block = Block::default().title("Username").borders(Borders::ALL);
+    let email_block = Block::default().title("Email").borders(Borders::ALL);
+    let token_block = Block::default().title("PA-Token").borders(Borders::ALL);
+    let nickname_block = Block::default()
         .title("Nickname (Optional)")
         .borders(Borders::ALL);
+    let host_block = Block::default()
+        .title("Host, e.g. github.com (Optional)")
+        .borders(Borders::ALL);
+    let provider_block = Block::default()
+        .title("Provider, e.g. gitlab (Optional, detected from host)")
+        .borders(Borders::ALL);
 
     let active_style = Style::default().bg(Color::LightYellow).fg(Color::Black);
 
-    match editing {
-        CurrentlyEditing::Alias => alias_block = alias_block.style(active_style),
-        CurrentlyEditing::Username => username_block = username_block.style(active_style),
-        CurrentlyEditing::Email => email_block = email_block.style(active_style),
-        CurrentlyEditing::Token => token_block = token_block.style(active_style),
-        CurrentlyEditing::Nickname => nickname_block = nickname_block.style(active_style),
-    };
-
-    let alias_text = Paragraph::new(app.alias_input.clone()).block(alias_block);
-    let username_text = Paragraph::new(app.username_input.clone()).block(username_block);
-    let email_text = Paragraph::new(app.email_input.clone()).block(email_block);
-    let token_text = Paragraph::new(app.token_input.clone()).block(token_block);
-    let nickname_text = Paragraph::new(app.nickname_input.clone()).block(nickname_block);
-    frame.render_widget(alias_text, popup_chunks[0]);
-    frame.render_widget(username_text, popup_chunks[1]);
-    frame.render_widget(email_text, popup_chunks[2]);
-    frame.render_widget(token_text, popup_chunks[3]);
-    frame.render_widget(nickname_text, popup_chunks[4]);
+    let fields = [
+        (CurrentlyEditing::Alias, alias_block, &app.alias_input),
+        (
+            CurrentlyEditing::Username,
+            username_block,
+            &app.username_input,
+        ),
+        (CurrentlyEditing::Email, email_block, &app.email_input),
+        (CurrentlyEditing::Token, token_block, &app.token_input),
+        (
+            CurrentlyEditing::Nickname,
+            nickname_block,
+            &app.nickname_input,
+        ),
+        (CurrentlyEditing::Host, host_block, &app.host_input),
+        (
+            CurrentlyEditing::Provider,
+            provider_block,
+            &app.provider_input,
+        ),
+    ];
+    for (idx, (field, block, input)) in fields.into_iter().enumerate() {
+        let focused = field == *editing;
+        let block = if focused {
+            block.style(active_style)
+        } else {
+            block
+        };
+        input.render(frame, popup_chunks[idx], block, focused);
+        app.click_map.add(popup_chunks[idx], Target::Field(field));
+    }
 }
@@ -0,0 +1,158 @@
+use crate::app::Entry;
+
+/*
+* The kind of forge a profile belongs to. It decides which user name the token
+* is presented with over https, for clones and the credential helper alike.
+*/
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Provider {
+    GitHub,
+    GitLab,
+    Gitea,
+    Bitbucket,
+    AzureDevOps,
+}
+
+impl Provider {
+    pub const ALL: [Provider; 5] = [
+        Provider::GitHub,
+        Provider::GitLab,
+        Provider::Gitea,
+        Provider::Bitbucket,
+        Provider::AzureDevOps,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Provider::GitHub => "github",
+            Provider::GitLab => "gitlab",
+            Provider::Gitea => "gitea",
+            Provider::Bitbucket => "bitbucket",
+            Provider::AzureDevOps => "azure",
+        }
+    }
+
+    pub fn from_label(label: &str) -> Option<Provider> {
+        Provider::ALL
+            .into_iter()
+            .find(|provider| provider.label().eq_ignore_ascii_case(label.trim()))
+    }
+
+    /*
+     * Guess the provider from the host. Self hosted GitLab instances usually have
+     * gitlab in their name, anything unknown is treated like Gitea, which
+     * accepts the same credentials as GitHub.
+     */
+    pub fn detect(host: &str) -> Provider {
+        let host = host.to_lowercase();
+        if host == "github.com" || host.ends_with(".github.com") {
+            Provider::GitHub
+        } else if host.contains("gitlab") {
+            Provider::GitLab
+        } else if host == "bitbucket.org" {
+            Provider::Bitbucket
+        } else if host == "dev.azure.com" || host.ends_with(".visualstudio.com") {
+            Provider::AzureDevOps
+        } else {
+            Provider::Gitea
+        }
+    }
+
+    // the provider set in the profile, or the one its host suggests
+    pub fn of(entry: &Entry) -> Provider {
+        entry
+            .provider
+            .unwrap_or_else(|| Provider::detect(&entry.host))
+    }
+
+    /*
+     * User name and password to present the token of the profile with.
+     * GitLab wants oauth2 as user for tokens, Azure DevOps ignores the user but
+     * needs one, the others take the account name and the token or app password.
+     */
+    pub fn credentials(&self, entry: &Entry) -> (String, String) {
+        let user = match self {
+            Provider::GitLab => "oauth2".to_string(),
+            Provider::AzureDevOps if entry.username.is_empty() => "pat".to_string(),
+            _ => entry.username.clone(),
+        };
+        (user, entry.pa_token.clone())
+    }
+}
+
+// a repository url split into the parts needed to clone it with a token
+#[derive(Clone, PartialEq, Debug)]
+pub struct RepoUrl {
+    pub host: String, // including a port, if any
+    pub path: String, // everything after the host
+    pub name: String, // directory git clones into
+}
+
+impl RepoUrl {
+    pub fn https(&self) -> String {
+        format!("https://{}/{}", self.host, self.path)
+    }
+}
+
+/*
+* Parse the url of a repository as copied from a forge: https urls with or
+* without user info and .git, nested groups like gitlab.com/a/b/c/repo, Azure
+* DevOps urls like dev.azure.com/org/project/_git/repo and scp-like ssh urls,
+* which are turned into https since tokens only work there. Azure DevOps ssh
+* urls map to the https url of the same repository.
+*/
+pub fn parse_clone_url(url: &str) -> Result<RepoUrl, String> {
+    let url = url.trim();
+    let rest = if let Some(rest) = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))
+        .or_else(|| url.strip_prefix("ssh://"))
+    {
+        rest.to_string()
+    } else if let Some((user_host, path)) = url
+        .split_once(':')
+        .filter(|(user_host, _)| !user_host.contains('/') && user_host.contains('@'))
+    {
+        // git@host:group/repo.git
+        format!("{}/{}", user_host, path)
+    } else {
+        return Err(format!(
+            "{} is not an https or ssh url of a repository",
+            url
+        ));
+    };
+    let (authority, path) = rest
+        .split_once('/')
+        .ok_or_else(|| format!("{} has no repository path", url))?;
+    let host = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host);
+    // the ssh port does not apply to https
+    let host = if url.starts_with("ssh://") {
+        host.split(':').next().unwrap_or(host)
+    } else {
+        host
+    };
+    let path = path.trim_end_matches('/');
+    // Azure DevOps ssh urls look like ssh.dev.azure.com:v3/org/project/repo
+    let (host, path) = match (host, path.strip_prefix("v3/")) {
+        ("ssh.dev.azure.com", Some(rest)) => match rest.split('/').collect::<Vec<_>>()[..] {
+            [org, project, repo] => (
+                "dev.azure.com",
+                format!("{}/{}/_git/{}", org, project, repo),
+            ),
+            _ => return Err(format!("{} is not an Azure DevOps repository", url)),
+        },
+        _ => (host, path.to_string()),
+    };
+    let name = path.rsplit('/').next().unwrap_or_default();
+    let name = name.strip_suffix(".git").unwrap_or(name);
+    if host.is_empty() || name.is_empty() {
+        return Err(format!("{} has no repository path", url));
+    }
+    Ok(RepoUrl {
+        host: host.to_string(),
+        name: name.to_string(),
+        path,
+    })
+}
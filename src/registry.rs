@@ -0,0 +1,85 @@
+use crate::inject::InjectScope;
+use crate::store;
+use crate::trash;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+// a repo a profile was injected into or cloned with
+#[derive(Clone)]
+pub struct RepoRecord {
+    pub alias: String,
+    pub scope: InjectScope,
+    pub path: PathBuf,
+    pub last_used: u64, // unix timestamp in seconds
+}
+
+/*
+* The registry lives next to the profile store, i.e. ~/.gat.repos for ~/.gat.
+*/
+pub fn registry_file(save_file: &Path) -> PathBuf {
+    let mut name = save_file.as_os_str().to_owned();
+    name.push(".repos");
+    PathBuf::from(name)
+}
+
+/*
+* Each line holds alias, scope, path and timestamp as escaped fields.
+* Records of repos that no longer exist are pruned while loading.
+*/
+pub fn load_registry(registry_file: &Path) -> Vec<RepoRecord> {
+    let Ok(content) = fs::read_to_string(registry_file) else {
+        return Vec::new();
+    };
+    let mut records = Vec::new();
+    for line in content.lines() {
+        let fields: Vec<String> = line.split(',').map(store::unescape_field).collect();
+        let [alias, scope, path, last_used] = fields.as_slice() else {
+            continue;
+        };
+        let (Some(scope), Ok(last_used)) = (InjectScope::from_label(scope), last_used.parse())
+        else {
+            continue;
+        };
+        let path = PathBuf::from(path);
+        if !path.exists() {
+            continue;
+        }
+        records.push(RepoRecord {
+            alias: alias.clone(),
+            scope,
+            path,
+            last_used,
+        });
+    }
+    records
+}
+
+pub fn save_registry(registry_file: &Path, records: &[RepoRecord]) -> io::Result<()> {
+    let mut content = String::new();
+    for record in records {
+        content.push_str(&format!(
+            "{},{},{},{}\n",
+            store::escape_field(&record.alias),
+            record.scope.label(),
+            store::escape_field(&record.path.to_string_lossy()),
+            record.last_used
+        ));
+    }
+    store::write_atomic(registry_file, &content)
+}
+
+/*
+* Remember that the profile was injected into the repo. A repo only uses one
+* profile per scope, so an older record for the same repo and scope is replaced.
+*/
+pub fn record(records: &mut Vec<RepoRecord>, alias: &str, scope: InjectScope, path: &Path) {
+    let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    records.retain(|record| !(record.path == path && record.scope == scope));
+    records.push(RepoRecord {
+        alias: alias.to_string(),
+        scope,
+        path,
+        last_used: trash::now(),
+    });
+}
@@ -0,0 +1,215 @@
+use crate::app::Entry;
+use crate::git::GitRunner;
+use crate::provider::Provider;
+use crate::scrub::{self, split_credentials};
+use crate::store;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use std::env;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+// base url replacing https://<host> in api requests, e.g. to validate against a local mock
+const API_OVERRIDE_VAR: &str = "GAT_FORGE_API";
+
+/*
+* Checks a token against the forge before it replaces the old one. A trait so
+* tests and offline setups can swap in their own.
+*/
+pub trait TokenValidator: Send + Sync {
+    fn validate(&self, entry: &Entry, token: &str) -> Result<(), String>;
+}
+
+/*
+* The user endpoint and auth header of the forge API of the profile, depending
+* on its provider.
+*/
+pub fn api_request(entry: &Entry, token: &str) -> (String, String) {
+    let base = |default: String| env::var(API_OVERRIDE_VAR).unwrap_or(default);
+    let host = &entry.host;
+    let basic = |user: &str| {
+        format!(
+            "Authorization: Basic {}",
+            STANDARD.encode(format!("{}:{}", user, token))
+        )
+    };
+    match Provider::of(entry) {
+        // GitHub Enterprise servers serve the API below /api/v3 of their own host
+        Provider::GitHub if host != "github.com" => (
+            format!("{}/api/v3/user", base(format!("https://{}", host))),
+            format!("Authorization: Bearer {}", token),
+        ),
+        Provider::GitHub => (
+            format!("{}/user", base("https://api.github.com".to_string())),
+            format!("Authorization: Bearer {}", token),
+        ),
+        Provider::GitLab => (
+            format!("{}/api/v4/user", base(format!("https://{}", host))),
+            format!("PRIVATE-TOKEN: {}", token),
+        ),
+        Provider::Gitea => (
+            format!("{}/api/v1/user", base(format!("https://{}", host))),
+            format!("Authorization: token {}", token),
+        ),
+        Provider::Bitbucket => (
+            format!("{}/2.0/user", base("https://api.bitbucket.org".to_string())),
+            basic(&entry.username),
+        ),
+        Provider::AzureDevOps => (
+            format!(
+                "{}/_apis/profile/profiles/me?api-version=7.1",
+                base("https://app.vssps.visualstudio.com".to_string())
+            ),
+            basic(""),
+        ),
+    }
+}
+
+/*
+* The status code curl writes after the response body with -w. The body is
+* discarded here rather than with -o /dev/null, which does not exist on Windows.
+*/
+pub fn http_status(stdout: &str) -> &str {
+    stdout.rsplit('\n').next().unwrap_or_default().trim()
+}
+
+// asks the forge API through curl who the token belongs to
+pub struct ForgeApi;
+
+impl TokenValidator for ForgeApi {
+    fn validate(&self, entry: &Entry, token: &str) -> Result<(), String> {
+        if entry.host.is_empty() {
+            return Err("the profile has no host to validate the token against".to_string());
+        }
+        let (url, header) = api_request(entry, token);
+        // the header goes through stdin to keep the token out of the process list
+        let mut curl = Command::new("curl")
+            .args(["-sS", "-w", "\n%{http_code}", "-H", "@-"])
+            .arg(&url)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|err| format!("Unable to run curl: {}", err))?;
+        if let Some(mut stdin) = curl.stdin.take() {
+            writeln!(stdin, "{}", header).map_err(|err| err.to_string())?;
+        }
+        let output = curl.wait_with_output().map_err(|err| err.to_string())?;
+        match http_status(&String::from_utf8_lossy(&output.stdout)) {
+            "200" => Ok(()),
+            "401" | "403" => Err(format!("{} rejected the token", entry.host)),
+            code => Err(format!(
+                "unexpected answer {} from {}: {}",
+                code,
+                url,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )),
+        }
+    }
+}
+
+/*
+* The url with the old token swapped for the new one, None if it does not carry
+* the old token.
+*/
+pub fn replace_token(url: &str, old: &str, new: &str) -> Option<String> {
+    let (clean_url, user, password) = split_credentials(url)?;
+    let userinfo = match password {
+        Some(password) if password == old => format!("{}:{}", user, new),
+        None if user == old => new.to_string(),
+        _ => return None,
+    };
+    let (scheme, rest) = clean_url.split_once("://")?;
+    Some(format!("{}://{}@{}", scheme, userinfo, rest))
+}
+
+#[derive(Default)]
+pub struct RotationReport {
+    pub remotes: Vec<(PathBuf, String)>, // repo and config key of every rewritten url
+    pub credentials: usize,              // lines rewritten in ~/.git-credentials
+    pub errors: Vec<String>,
+}
+
+impl RotationReport {
+    pub fn summary(&self, alias: &str) -> Vec<String> {
+        let mut lines = vec![format!("The token of {} was replaced", alias)];
+        lines.push(format!(
+            "{} remote urls rewritten, {} saved credentials updated",
+            self.remotes.len(),
+            self.credentials
+        ));
+        for (repo, key) in &self.remotes {
+            lines.push(format!("  {} {}", repo.display(), key));
+        }
+        for error in &self.errors {
+            lines.push(format!("Failed: {}", error));
+        }
+        lines
+    }
+}
+
+/*
+* Rewrite the lines of a git credential store file carrying the old token.
+*/
+pub fn update_credentials_file(path: &Path, old: &str, new: &str) -> io::Result<usize> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(0),
+        Err(err) => return Err(err),
+    };
+    let mut updated = 0;
+    let mut new_content = String::new();
+    for line in content.lines() {
+        match replace_token(line, old, new) {
+            Some(line) => {
+                updated += 1;
+                new_content.push_str(&line);
+            }
+            None => new_content.push_str(line),
+        }
+        new_content.push('\n');
+    }
+    if updated > 0 {
+        store::write_atomic(path, &new_content)?;
+    }
+    Ok(updated)
+}
+
+/*
+* Replace the token of the entry with the new one in the remotes of the given
+* repos and in the credential store file. The entry itself is left to the caller.
+*/
+pub fn rotate(
+    git: &dyn GitRunner,
+    entry: &Entry,
+    new_token: &str,
+    repos: &[PathBuf],
+    credentials_file: &Path,
+) -> RotationReport {
+    let mut report = RotationReport::default();
+    let old = &entry.pa_token;
+    for repo in repos {
+        let Ok(findings) = scrub::find(git, repo, std::slice::from_ref(entry)) else {
+            continue;
+        };
+        // only urls carrying this profile's token, not just any credentials
+        for finding in findings.iter().filter(|finding| finding.profile.is_some()) {
+            let Some(new_url) = replace_token(&finding.url, old, new_token) else {
+                continue;
+            };
+            match scrub::rewrite(git, finding, &new_url) {
+                Ok(()) => report.remotes.push((repo.clone(), finding.key.clone())),
+                Err(err) => report.errors.push(format!("{}: {}", repo.display(), err)),
+            }
+        }
+    }
+    match update_credentials_file(credentials_file, old, new_token) {
+        Ok(updated) => report.credentials = updated,
+        Err(err) => report
+            .errors
+            .push(format!("{}: {}", credentials_file.display(), err)),
+    }
+    report
+}
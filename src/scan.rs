@@ -0,0 +1,145 @@
+use crate::git::GitRunner;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use std::thread;
+
+// directories that never contain repos worth scanning but can be huge
+const SKIPPED_DIRS: [&str; 3] = ["node_modules", "target", ".cache"];
+
+pub struct ScannedRepo {
+    pub path: PathBuf,
+    pub name: Option<String>,  // effective user.name in the repo
+    pub email: Option<String>, // effective user.email in the repo
+    pub remote_host: Option<String>,
+    pub profile: Option<String>, // alias of the profile to inject, initially the suggested one
+    pub selected: bool,
+}
+
+pub enum ScanMessage {
+    Progress { done: usize, total: usize },
+    Found(ScannedRepo),
+    Finished,
+}
+
+/*
+* Extract the host from the usual remote url formats:
+* https://[user[:token]@]host[:port]/path, ssh://[user@]host[:port]/path and user@host:path
+*/
+pub fn remote_host(url: &str) -> Option<String> {
+    let url = url.trim();
+    let rest = match url.split_once("://") {
+        Some((_, rest)) => rest,
+        None => url.split_once(':')?.0,
+    };
+    let authority = rest.split('/').next()?;
+    let host = authority.rsplit('@').next()?;
+    let host = host.split(':').next()?;
+    if host.is_empty() {
+        None
+    } else {
+        Some(host.to_lowercase())
+    }
+}
+
+fn git_value(git: &dyn GitRunner, repo: &Path, args: &[&str]) -> Option<String> {
+    let output = git.run(repo, args).ok()?;
+    let value = output.stdout.trim();
+    if output.success && !value.is_empty() {
+        Some(value.to_string())
+    } else {
+        None
+    }
+}
+
+/*
+* Read the identity and remote host of a single repo.
+*/
+pub fn inspect_repo(git: &dyn GitRunner, repo: &Path) -> ScannedRepo {
+    ScannedRepo {
+        path: repo.to_path_buf(),
+        name: git_value(git, repo, &["config", "--get", "user.name"]),
+        email: git_value(git, repo, &["config", "--get", "user.email"]),
+        remote_host: git_value(git, repo, &["remote", "get-url", "origin"])
+            .and_then(|url| remote_host(&url)),
+        profile: None,
+        selected: false,
+    }
+}
+
+fn walk(dir: &Path, git: &dyn GitRunner, sender: &Sender<ScanMessage>) {
+    if dir.join(".git").exists() {
+        let _ = sender.send(ScanMessage::Found(inspect_repo(git, dir)));
+        // nested repos are submodules or vendored code, leave them alone
+        return;
+    }
+    let Ok(children) = fs::read_dir(dir) else {
+        return;
+    };
+    for child in children.flatten() {
+        let name = child.file_name();
+        let name = name.to_string_lossy();
+        if name.starts_with('.') || SKIPPED_DIRS.contains(&name.as_ref()) {
+            continue;
+        }
+        // file_type does not follow symlinks, which keeps the walk free of cycles
+        if child.file_type().map(|kind| kind.is_dir()).unwrap_or(false) {
+            walk(&child.path(), git, sender);
+        }
+    }
+}
+
+/*
+* Walk the tree below root on a background thread and report every git repo found.
+* Progress is measured in top level directories of root.
+*/
+pub fn start_scan(root: PathBuf, git: Arc<dyn GitRunner>) -> Receiver<ScanMessage> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        if root.join(".git").exists() {
+            walk(&root, git.as_ref(), &sender);
+            let _ = sender.send(ScanMessage::Finished);
+            return;
+        }
+        let mut top_level: Vec<PathBuf> = fs::read_dir(&root)
+            .map(|children| {
+                children
+                    .flatten()
+                    .filter(|child| child.file_type().map(|kind| kind.is_dir()).unwrap_or(false))
+                    .map(|child| child.path())
+                    .collect()
+            })
+            .unwrap_or_default();
+        top_level.sort();
+        let total = top_level.len();
+        for (done, dir) in top_level.iter().enumerate() {
+            let hidden = dir
+                .file_name()
+                .map(|name| name.to_string_lossy().starts_with('.'))
+                .unwrap_or(false);
+            if !hidden {
+                walk(dir, git.as_ref(), &sender);
+            }
+            let _ = sender.send(ScanMessage::Progress {
+                done: done + 1,
+                total,
+            });
+        }
+        let _ = sender.send(ScanMessage::Finished);
+    });
+    receiver
+}
+
+// every repo below root, collected from a scan run to completion
+pub fn scan_all(root: &Path, git: Arc<dyn GitRunner>) -> Vec<ScannedRepo> {
+    let mut repos = Vec::new();
+    for message in start_scan(root.to_path_buf(), git) {
+        match message {
+            ScanMessage::Found(repo) => repos.push(repo),
+            ScanMessage::Progress { .. } => {}
+            ScanMessage::Finished => break,
+        }
+    }
+    repos
+}
@@ -0,0 +1,178 @@
+use crate::app::{App, JSON_VERSION};
+use crate::inject::InjectScope;
+use crate::scan;
+use serde::Serialize;
+use std::path::PathBuf;
+
+/*
+* The hook `gat shell-init <shell>` prints for the shell's rc file. It runs
+* `gat status --apply --quiet` whenever the working directory changes.
+*/
+pub fn init_script(shell: &str) -> Result<&'static str, String> {
+    match shell {
+        "bash" => Ok(r#"_gat_hook() {
+  if [ "$PWD" != "$_GAT_LAST_PWD" ]; then
+    _GAT_LAST_PWD="$PWD"
+    command gat status --apply --quiet
+  fi
+}
+case ";${PROMPT_COMMAND:-};" in
+  *";_gat_hook;"*) ;;
+  *) PROMPT_COMMAND="_gat_hook${PROMPT_COMMAND:+;$PROMPT_COMMAND}" ;;
+esac
+"#),
+        "zsh" => Ok(r#"_gat_hook() {
+  command gat status --apply --quiet
+}
+autoload -Uz add-zsh-hook
+add-zsh-hook chpwd _gat_hook
+_gat_hook
+"#),
+        "fish" => Ok(r#"function __gat_hook --on-variable PWD
+  command gat status --apply --quiet
+end
+__gat_hook
+"#),
+        _ => Err(format!(
+            "unsupported shell {}, use bash, zsh or fish",
+            shell
+        )),
+    }
+}
+
+// why a profile is expected in a repo
+#[derive(Clone, PartialEq)]
+pub enum Binding {
+    Registry(InjectScope), // it was injected there before
+    Remote(String),        // its host matches the remote of the repo
+}
+
+pub struct RepoStatus {
+    pub root: PathBuf,
+    pub name: Option<String>,  // effective user.name
+    pub email: Option<String>, // effective user.email
+    pub active: Option<usize>, // profile whose identity is configured
+    pub bound: Option<(usize, Binding)>,
+}
+
+// output of `gat status --json`, versioned with the profiles, see JSON_VERSION
+#[derive(Serialize)]
+pub struct StatusJson {
+    pub version: u32,
+    pub repo: Option<PathBuf>, // null outside of repos, everything else is empty then
+    pub branch: Option<String>,
+    pub name: Option<String>,
+    pub email: Option<String>,
+    pub active: Option<String>,   // alias of the profile in use
+    pub bound: Option<String>,    // alias of the profile the repo should use
+    pub bound_by: Option<String>, // "registry" or "remote"
+    pub mismatch: bool,
+    pub coauthors: Vec<String>, // trailers of the pairing session
+}
+
+impl StatusJson {
+    pub fn new(app: &App, status: Option<&RepoStatus>) -> StatusJson {
+        let alias = |idx: usize| app.entries[idx].alias.clone();
+        StatusJson {
+            version: JSON_VERSION,
+            repo: status.map(|status| status.root.clone()),
+            branch: app.target.as_ref().and_then(|target| target.branch.clone()),
+            name: status.and_then(|status| status.name.clone()),
+            email: status.and_then(|status| status.email.clone()),
+            active: status.and_then(|status| status.active).map(alias),
+            bound: status
+                .and_then(|status| status.bound.as_ref())
+                .map(|(idx, _)| alias(*idx)),
+            bound_by: status
+                .and_then(|status| status.bound.as_ref())
+                .map(|(_, binding)| match binding {
+                    Binding::Registry(_) => "registry".to_string(),
+                    Binding::Remote(_) => "remote".to_string(),
+                }),
+            mismatch: status.is_some_and(|status| status.mismatch()),
+            coauthors: app.coauthors.clone(),
+        }
+    }
+}
+
+impl RepoStatus {
+    // the identity in use differs from the one of the bound profile
+    pub fn mismatch(&self) -> bool {
+        matches!(&self.bound, Some((bound, _)) if self.active != Some(*bound))
+    }
+}
+
+/*
+* Work out the identity used in the target repo and the profile it should use.
+* The registry entry for the closest enclosing directory wins over the remote
+* host. None outside of repos.
+*/
+pub fn repo_status(app: &App) -> Option<RepoStatus> {
+    let root = app.target.as_ref()?.root.clone();
+    let config = |key: &str| {
+        app.git
+            .run(&app.workdir, &["config", "--get", key])
+            .ok()
+            .filter(|output| output.success)
+            .map(|output| output.stdout.trim().to_string())
+            .filter(|value| !value.is_empty())
+    };
+    let name = config("user.name");
+    let email = config("user.email");
+    let active = app.entries.iter().position(|entry| {
+        email
+            .as_deref()
+            .is_some_and(|email| entry.email.eq_ignore_ascii_case(email))
+            && name.as_deref() == Some(entry.author_name().as_str())
+    });
+    let registered = app
+        .repos
+        .iter()
+        .filter(|record| {
+            record.scope != InjectScope::Global && app.workdir.starts_with(&record.path)
+        })
+        .filter_map(|record| {
+            let idx = app
+                .entries
+                .iter()
+                .position(|entry| entry.alias == record.alias)?;
+            Some((
+                record.path.components().count(),
+                record.last_used,
+                idx,
+                record.scope,
+            ))
+        })
+        .max_by_key(|(depth, last_used, _, _)| (*depth, *last_used))
+        .map(|(_, _, idx, scope)| (idx, Binding::Registry(scope)));
+    let bound = registered.or_else(|| {
+        let host = config("remote.origin.url").and_then(|url| scan::remote_host(&url))?;
+        let idx = app.suggest_profile(Some(&host))?;
+        Some((idx, Binding::Remote(host)))
+    });
+    Some(RepoStatus {
+        root,
+        name,
+        email,
+        active,
+        bound,
+    })
+}
+
+/*
+* The text `gat prompt` prints: the alias of the active profile, followed by
+* a '!' if the repo is bound to another one. Empty outside of repos.
+*/
+pub fn prompt(app: &App, status: Option<&RepoStatus>) -> String {
+    let Some(status) = status else {
+        return String::new();
+    };
+    let mut prompt = status
+        .active
+        .map(|idx| app.entries[idx].alias.clone())
+        .unwrap_or_default();
+    if status.mismatch() {
+        prompt.push('!');
+    }
+    prompt
+}
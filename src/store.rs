@@ -0,0 +1,342 @@
+use crate::app::Entry;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+// number of rolling backups kept next to the store, i.e. ~/.gat.bak.1 to ~/.gat.bak.5
+pub const BACKUP_COUNT: usize = 5;
+
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+pub fn backup_file(path: &Path, generation: usize) -> PathBuf {
+    sibling(path, &format!(".bak.{}", generation))
+}
+
+/*
+* Exclusive lock on the store, released when dropped.
+* Every read-compare-write cycle on the store happens while holding it, so two
+* gat instances can never interleave their saves.
+*/
+pub struct StoreLock {
+    _file: File,
+}
+
+impl StoreLock {
+    pub fn acquire(path: &Path) -> io::Result<StoreLock> {
+        let file = File::options()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(sibling(path, ".lock"))?;
+        file.lock()?;
+        Ok(StoreLock { _file: file })
+    }
+}
+
+/*
+* Create or truncate a file readable by its owner only. An existing file is
+* restricted before anything is written to it.
+*/
+pub fn create_private(path: &Path) -> io::Result<File> {
+    let mut options = File::options();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let file = options.open(path)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(fs::Permissions::from_mode(0o600))?;
+    }
+    Ok(file)
+}
+
+/*
+* Write the content to a temporary file in the same directory and rename it over
+* the target, so the target either holds the old or the new content but never
+* a partially written file. The files written this way hold tokens, so the
+* temporary file is private and only gets the permissions of an existing target
+* once it is complete.
+*/
+pub fn write_atomic(path: &Path, content: &str) -> io::Result<()> {
+    let tmp_path = sibling(path, ".tmp");
+    let mut tmp = create_private(&tmp_path)?;
+    tmp.write_all(content.as_bytes())?;
+    tmp.sync_all()?;
+    if let Ok(metadata) = fs::metadata(path) {
+        tmp.set_permissions(metadata.permissions())?;
+    }
+    fs::rename(&tmp_path, path)
+}
+
+/*
+* Shift the existing backups by one generation and copy the current store to
+* the first one. The oldest backup falls off the end.
+*/
+pub fn rotate_backups(path: &Path) -> io::Result<()> {
+    if !path.exists() {
+        return Ok(());
+    }
+    for generation in (1..BACKUP_COUNT).rev() {
+        let from = backup_file(path, generation);
+        if from.exists() {
+            fs::rename(&from, backup_file(path, generation + 1))?;
+        }
+    }
+    fs::copy(path, backup_file(path, 1))?;
+    Ok(())
+}
+
+/*
+* Escape a value for the key=value fields following the fixed profile columns.
+* Commas separate fields and the first '=' separates key and value, so both are
+* percent encoded along with '%' itself and line breaks.
+*/
+pub fn escape_field(value: &str) -> String {
+    let mut escaped = String::new();
+    for c in value.chars() {
+        match c {
+            '%' => escaped.push_str("%25"),
+            ',' => escaped.push_str("%2C"),
+            '=' => escaped.push_str("%3D"),
+            '\n' => escaped.push_str("%0A"),
+            '\r' => escaped.push_str("%0D"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+pub fn unescape_field(value: &str) -> String {
+    let mut unescaped = String::new();
+    let mut rest = value;
+    while let Some(idx) = rest.find('%') {
+        unescaped.push_str(&rest[..idx]);
+        let code = rest
+            .get(idx + 1..idx + 3)
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match code {
+            Some(byte) => {
+                unescaped.push(byte as char);
+                rest = &rest[idx + 3..];
+            }
+            None => {
+                unescaped.push('%');
+                rest = &rest[idx + 1..];
+            }
+        }
+    }
+    unescaped.push_str(rest);
+    unescaped
+}
+
+pub fn parse_entries(content: &str) -> Vec<Entry> {
+    content
+        .lines()
+        .filter(|line| !line.is_empty())
+        .filter_map(Entry::from_line)
+        .collect()
+}
+
+pub fn serialize_entries(entries: &[Entry]) -> String {
+    let mut content = String::new();
+    for entry in entries {
+        content.push_str(format!("{}\n", entry).as_str());
+    }
+    content
+}
+
+/*
+* Whether two store contents only differ in when profiles were last used. Such
+* writes happen on every injection and are not worth a backup generation.
+*/
+pub fn differs_only_in_usage(a: &str, b: &str) -> bool {
+    let without_usage = |content: &str| {
+        let mut entries = parse_entries(content);
+        for entry in &mut entries {
+            entry.last_used = 0;
+        }
+        serialize_entries(&entries)
+    };
+    without_usage(a) == without_usage(b)
+}
+
+/*
+* Three way merge of the profiles, keyed by alias.
+* base is what was loaded at startup, disk what another instance saved since then
+* and ours the in memory state. Our changes win over changes on disk, profiles
+* only touched on disk are taken from there.
+*/
+pub fn merge_entries(base: &[Entry], disk: &[Entry], ours: &[Entry]) -> Vec<Entry> {
+    let find = |entries: &[Entry], alias: &str| -> Option<String> {
+        entries
+            .iter()
+            .find(|entry| entry.alias == alias)
+            .map(|entry| entry.to_string())
+    };
+    let mut merged = Vec::new();
+    for entry in ours {
+        let in_base = find(base, &entry.alias);
+        let on_disk = find(disk, &entry.alias);
+        let unchanged_by_us = in_base.as_deref() == Some(entry.to_string().as_str());
+        match (unchanged_by_us, on_disk) {
+            // deleted by the other instance
+            (true, None) => {}
+            (true, Some(disk_line)) => {
+                merged.push(Entry::from_line(&disk_line).unwrap_or_else(|| entry.clone()))
+            }
+            (false, _) => merged.push(entry.clone()),
+        }
+    }
+    for entry in disk {
+        let in_base = find(base, &entry.alias).is_some();
+        let in_merged = merged.iter().any(|merged| merged.alias == entry.alias);
+        // added by the other instance
+        if !in_base && !in_merged {
+            merged.push(entry.clone());
+        }
+    }
+    merged
+}
+
+// environment variables overriding the store and naming read-only stores shared by a team
+pub const STORE_VAR: &str = "GAT_STORE";
+pub const SHARED_STORES_VAR: &str = "GAT_SHARED_STORES";
+
+/*
+* The store to use, in order of precedence: the --store flag, GAT_STORE,
+* $XDG_CONFIG_HOME/gat/store (~/.config/gat/store) if it exists and ~/.gat.
+* The environment is passed in so the order can be tested.
+*/
+pub fn resolve_store(
+    flag: Option<PathBuf>,
+    store_var: Option<PathBuf>,
+    xdg_config_home: Option<PathBuf>,
+    home_dir: Option<&Path>,
+) -> Result<PathBuf, String> {
+    if let Some(path) = flag
+        .or(store_var)
+        .filter(|path| !path.as_os_str().is_empty())
+    {
+        return Ok(path);
+    }
+    let config_dir = xdg_config_home
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| home_dir.map(|home| home.join(".config")));
+    if let Some(xdg_store) = config_dir.map(|dir| dir.join("gat").join("store")) {
+        if xdg_store.is_file() {
+            return Ok(xdg_store);
+        }
+    }
+    home_dir.map(|home| home.join(".gat")).ok_or_else(|| {
+        format!(
+            "there is no home directory, choose a store with --store or {}",
+            STORE_VAR
+        )
+    })
+}
+
+// the shared stores given as flags followed by the ones listed in GAT_SHARED_STORES
+pub fn shared_stores(flags: Vec<PathBuf>) -> Vec<PathBuf> {
+    let mut stores = flags;
+    if let Some(list) = std::env::var_os(SHARED_STORES_VAR) {
+        stores.extend(std::env::split_paths(&list).filter(|path| !path.as_os_str().is_empty()));
+    }
+    stores
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use std::thread;
+    use std::time::Duration;
+
+    // an empty directory below the system temp dir, removed by each test
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("gat-store-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn failed_writes_leave_the_target_intact() {
+        let dir = temp_dir("failed");
+        let path = dir.join(".gat");
+        write_atomic(&path, "work,jdoe,j@doe.dev,tok,\n").unwrap();
+        // the temporary file cannot be created where a directory is in the way
+        fs::create_dir(sibling(&path, ".tmp")).unwrap();
+        assert!(write_atomic(&path, "garbage").is_err());
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "work,jdoe,j@doe.dev,tok,\n"
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn backups_rotate_and_the_oldest_falls_off() {
+        let dir = temp_dir("backups");
+        let path = dir.join(".gat");
+        // nothing to back up before the first save
+        rotate_backups(&path).unwrap();
+        assert!(!backup_file(&path, 1).exists());
+
+        let saves = BACKUP_COUNT + 2;
+        for save in 0..saves {
+            rotate_backups(&path).unwrap();
+            write_atomic(&path, &format!("save {}\n", save)).unwrap();
+        }
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            format!("save {}\n", saves - 1)
+        );
+        for generation in 1..=BACKUP_COUNT {
+            assert_eq!(
+                fs::read_to_string(backup_file(&path, generation)).unwrap(),
+                format!("save {}\n", saves - 1 - generation)
+            );
+        }
+        assert!(!backup_file(&path, BACKUP_COUNT + 1).exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn new_files_are_private_even_over_a_stale_temporary_file() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = temp_dir("private");
+        let path = dir.join(".gat.trash");
+        let tmp = sibling(&path, ".tmp");
+        fs::write(&tmp, "left over").unwrap();
+        fs::set_permissions(&tmp, fs::Permissions::from_mode(0o644)).unwrap();
+
+        write_atomic(&path, "1700000000,work,jdoe,j@doe.dev,tok,\n").unwrap();
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn a_second_writer_waits_for_the_lock() {
+        let dir = temp_dir("lock");
+        let path = dir.join(".gat");
+        let lock = StoreLock::acquire(&path).unwrap();
+        let (locked, waiting) = mpsc::channel();
+        let second = path.clone();
+        let writer = thread::spawn(move || {
+            let _lock = StoreLock::acquire(&second).unwrap();
+            locked.send(()).unwrap();
+        });
+        assert!(waiting.recv_timeout(Duration::from_millis(100)).is_err());
+        drop(lock);
+        waiting.recv_timeout(Duration::from_secs(5)).unwrap();
+        writer.join().unwrap();
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
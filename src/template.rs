@@ -0,0 +1,130 @@
+use crate::app::Entry;
+use crate::provider::Provider;
+use crate::store;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/*
+* The parts profiles of one kind share, e.g. the company email domain, the forge
+* host and signing settings. New profiles can be started from a template
+* instead of from scratch.
+*/
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Template {
+    pub name: String,
+    pub email_domain: String, // without the '@'
+    pub host: String,
+    pub provider: Option<Provider>,
+    pub git_config: Vec<(String, String)>,
+}
+
+impl Template {
+    // a template named after the profile, without anything personal like the token
+    pub fn from_entry(entry: &Entry) -> Template {
+        Template {
+            name: entry.alias.clone(),
+            email_domain: entry
+                .email
+                .rsplit_once('@')
+                .map(|(_, domain)| domain.to_string())
+                .unwrap_or_default(),
+            host: entry.host.clone(),
+            provider: entry.provider,
+            git_config: entry.git_config.clone(),
+        }
+    }
+
+    // a profile pre-filled from the template, the email waits for its local part
+    pub fn seed(&self) -> Entry {
+        Entry {
+            email: if self.email_domain.is_empty() {
+                String::new()
+            } else {
+                format!("@{}", self.email_domain)
+            },
+            host: self.host.clone(),
+            provider: self.provider,
+            git_config: self.git_config.clone(),
+            ..Entry::default()
+        }
+    }
+
+    // a line for the template picker
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if !self.email_domain.is_empty() {
+            parts.push(format!("@{}", self.email_domain));
+        }
+        if !self.host.is_empty() {
+            parts.push(self.host.clone());
+        }
+        if let Some(provider) = self.provider {
+            parts.push(provider.label().to_string());
+        }
+        if !self.git_config.is_empty() {
+            parts.push(format!("{} git config", self.git_config.len()));
+        }
+        parts.join(", ")
+    }
+
+    fn to_line(&self) -> String {
+        let mut line = format!(
+            "{},{},{},{}",
+            store::escape_field(&self.name),
+            store::escape_field(&self.email_domain),
+            store::escape_field(&self.host),
+            self.provider.map_or("", |provider| provider.label())
+        );
+        for (key, value) in &self.git_config {
+            line.push_str(&format!(
+                ",{}={}",
+                store::escape_field(key),
+                store::escape_field(value)
+            ));
+        }
+        line.push('\n');
+        line
+    }
+
+    fn from_line(line: &str) -> Option<Template> {
+        let mut fields = line.split(',');
+        let name = store::unescape_field(fields.next()?);
+        let email_domain = store::unescape_field(fields.next()?);
+        let host = store::unescape_field(fields.next()?);
+        let provider = Provider::from_label(fields.next()?);
+        let git_config = fields
+            .filter_map(|field| field.split_once('='))
+            .map(|(key, value)| (store::unescape_field(key), store::unescape_field(value)))
+            .collect();
+        Some(Template {
+            name,
+            email_domain,
+            host,
+            provider,
+            git_config,
+        })
+    }
+}
+
+/*
+* Templates live next to the profile store, i.e. ~/.gat.templates for ~/.gat.
+*/
+pub fn templates_file(save_file: &Path) -> PathBuf {
+    let mut name = save_file.as_os_str().to_owned();
+    name.push(".templates");
+    PathBuf::from(name)
+}
+
+pub fn load_templates(templates_file: &Path) -> Vec<Template> {
+    fs::read_to_string(templates_file)
+        .unwrap_or_default()
+        .lines()
+        .filter_map(Template::from_line)
+        .collect()
+}
+
+pub fn save_templates(templates_file: &Path, templates: &[Template]) -> io::Result<()> {
+    let content: String = templates.iter().map(Template::to_line).collect();
+    store::write_atomic(templates_file, &content)
+}
@@ -0,0 +1,79 @@
+use crate::app::Entry;
+use crate::store;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// deleted profiles are kept for this many days unless GAT_TRASH_DAYS says otherwise
+pub const DEFAULT_RETENTION_DAYS: u64 = 30;
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+pub struct TrashedEntry {
+    pub entry: Entry,
+    pub deleted_at: u64, // unix timestamp in seconds
+}
+
+impl TrashedEntry {
+    pub fn days_in_trash(&self) -> u64 {
+        now().saturating_sub(self.deleted_at) / SECONDS_PER_DAY
+    }
+}
+
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+pub fn retention_days() -> u64 {
+    std::env::var("GAT_TRASH_DAYS")
+        .ok()
+        .and_then(|days| days.parse().ok())
+        .unwrap_or(DEFAULT_RETENTION_DAYS)
+}
+
+/*
+* The trash lives next to the profile store, i.e. ~/.gat.trash for ~/.gat.
+*/
+pub fn trash_file(save_file: &Path) -> PathBuf {
+    let mut name = save_file.as_os_str().to_owned();
+    name.push(".trash");
+    PathBuf::from(name)
+}
+
+/*
+* Each line holds the deletion timestamp followed by the profile in store format.
+* Entries older than retention_days are purged while loading.
+*/
+pub fn load_trash(trash_file: &Path, retention_days: u64) -> Vec<TrashedEntry> {
+    let Ok(content) = fs::read_to_string(trash_file) else {
+        return Vec::new();
+    };
+    let oldest = now().saturating_sub(retention_days * SECONDS_PER_DAY);
+    let mut trash = Vec::new();
+    for line in content.lines() {
+        let Some((deleted_at, entry_line)) = line.split_once(',') else {
+            continue;
+        };
+        let Ok(deleted_at) = deleted_at.parse::<u64>() else {
+            continue;
+        };
+        if deleted_at < oldest {
+            continue;
+        }
+        if let Some(entry) = Entry::from_line(entry_line) {
+            trash.push(TrashedEntry { entry, deleted_at });
+        }
+    }
+    trash
+}
+
+pub fn save_trash(trash_file: &Path, trash: &[TrashedEntry]) -> io::Result<()> {
+    let mut content = String::new();
+    for trashed in trash {
+        content.push_str(format!("{},{}\n", trashed.deleted_at, trashed.entry).as_str());
+    }
+    store::write_atomic(trash_file, &content)
+}
@@ -10,30 +10,115 @@ use crate::main_menu::render_main_menu;
 use crate::popups::render_active_popups;
 
 pub fn ui(frame: &mut Frame, app: &App) {
+    app.click_map.clear();
     render_main_menu(frame, app);
     render_active_popups(frame, app);
 }
 
+// the keys of the main screen, as listed by the help popup
+pub const MAIN_KEYS: [(&str, &str); 24] = [
+    ("j/k", "select profile"),
+    ("Enter", "use profile in the repo"),
+    ("c", "clone a repo as the profile"),
+    ("%", "create a new profile"),
+    ("e", "edit selected profile"),
+    ("y", "duplicate selected profile"),
+    ("d", "delete selected profile"),
+    ("v", "reveal token"),
+    ("J/K", "move profile"),
+    ("f", "pin profile"),
+    ("S", "sort"),
+    ("g", "git config"),
+    ("x", "remove from repo"),
+    ("s", "scan repos"),
+    ("w", "wipe tokens from remotes"),
+    ("r", "rotate token"),
+    ("i", "import profiles"),
+    ("b", "import bundle"),
+    ("o", "other repo"),
+    ("p", "pair"),
+    ("l", "audit log"),
+    ("u", "undo"),
+    ("t", "trash"),
+    ("q", "quit"),
+];
+
 pub fn key_hints<'a>(current_screen: &CurrentScreen) -> Span<'a> {
     match current_screen {
         CurrentScreen::Main => Span::styled(
-            "(q) to quit / (%) to create a new profile / (d) to delete selected profile",
+            "(q) to quit / (%) new profile / (?) all keys",
             Style::default().fg(Color::Red),
         ),
+        CurrentScreen::Help => {
+            Span::styled("(any key) close", Style::default().fg(Color::Red))
+        }
         CurrentScreen::Editing => Span::styled(
             "(ESC) to cancel/(Tab) to switch boxes/(Enter) to complete",
             Style::default().fg(Color::Red),
         ),
-        CurrentScreen::Deleting => {
+        CurrentScreen::Deleting | CurrentScreen::Propagating => {
             Span::styled("(y) confirm/ (n) abort", Style::default().fg(Color::Red))
         }
         CurrentScreen::Cloning => Span::styled(
             "(Enter) confirm/ (Esc) abort",
             Style::default().fg(Color::Red),
         ),
-        CurrentScreen::Injecting => {
-            Span::styled("(y) confirm/ (n) abort", Style::default().fg(Color::Red))
-        }
+        CurrentScreen::Injecting | CurrentScreen::Removing => Span::styled(
+            "(Tab) change scope/ (y) confirm/ (n) abort",
+            Style::default().fg(Color::Red),
+        ),
+        CurrentScreen::Rotating => Span::styled(
+            "(Tab) toggle validation/ (Enter) confirm/ (Esc) back",
+            Style::default().fg(Color::Red),
+        ),
+        CurrentScreen::Scrubbing => Span::styled(
+            "(Tab) toggle credential helper/ (y) confirm/ (n) abort",
+            Style::default().fg(Color::Red),
+        ),
+        CurrentScreen::Bundle => Span::styled(
+            "(Tab) change conflict handling/ (Enter) continue/ (Esc) back",
+            Style::default().fg(Color::Red),
+        ),
+        CurrentScreen::Importing => Span::styled(
+            "(Space) select/ (a) select all/ (Enter) import/ (r) other directory/ (Esc) back",
+            Style::default().fg(Color::Red),
+        ),
+        CurrentScreen::Scanning => Span::styled(
+            "(Space) select/ (a) select suggested/ (p) change profile/ (Enter) inject/ (r) new scan/ (Esc) back",
+            Style::default().fg(Color::Red),
+        ),
+        CurrentScreen::ConfigEditor => Span::styled(
+            "(a) add key/ (d) remove key/ (Tab) switch boxes/ (Enter) save/ (Esc) back",
+            Style::default().fg(Color::Red),
+        ),
+        CurrentScreen::Quitting => Span::styled(
+            "(s) save and quit/ (d) discard and quit/ (Esc) back",
+            Style::default().fg(Color::Red),
+        ),
+        CurrentScreen::Conflict => Span::styled(
+            "(m) merge changes/ (r) reload from disk",
+            Style::default().fg(Color::Red),
+        ),
+        CurrentScreen::AuditLog => Span::styled(
+            "(type) filter/ (Up/Down) move/ (Esc) back",
+            Style::default().fg(Color::Red),
+        ),
+        CurrentScreen::ChoosingTemplate => Span::styled(
+            "(Enter) start from template/ (s) save selected profile as template/ (d) delete template/ (Esc) back",
+            Style::default().fg(Color::Red),
+        ),
+        CurrentScreen::Pairing => Span::styled(
+            "(Space) select co-author/ (Enter) start pairing/ (e) end pairing/ (Esc) back",
+            Style::default().fg(Color::Red),
+        ),
+        CurrentScreen::ChoosingRepo => Span::styled(
+            "(Tab) complete path/ (Enter) use this repo/ (Esc) back",
+            Style::default().fg(Color::Red),
+        ),
+        CurrentScreen::Trash => Span::styled(
+            "(r) restore/ (d) delete forever/ (u) undo/ (Esc) back",
+            Style::default().fg(Color::Red),
+        ),
     }
 }
 
@@ -43,9 +128,9 @@ pub fn fixed_size_centered_rect(width: u16, height: u16, r: Rect) -> Rect {
     let popup_layout = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(r.height / 2 - height / 2),
+            Constraint::Fill(1),
             Constraint::Length(height),
-            Constraint::Length(r.height / 2 - height / 2),
+            Constraint::Fill(1),
         ])
         .split(r);
 
@@ -53,9 +138,9 @@ pub fn fixed_size_centered_rect(width: u16, height: u16, r: Rect) -> Rect {
     Layout::default()
         .direction(Direction::Horizontal)
         .constraints([
-            Constraint::Length(r.width / 2 - width / 2),
+            Constraint::Fill(1),
             Constraint::Length(width),
-            Constraint::Length(r.width / 2 - width / 2),
+            Constraint::Fill(1),
         ])
         .split(popup_layout[1])[1] // Return the middle chunk
 }
@@ -0,0 +1,126 @@
+mod common;
+
+use common::test_app;
+use gat::app::App;
+use gat::audit::{self, Action, AuditEvent};
+use gat::cli::list_command;
+use gat::handler::handle_key_press;
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::path::Path;
+
+fn press(app: &mut App, code: KeyCode) {
+    handle_key_press(KeyEvent::new(code, KeyModifiers::NONE), app);
+}
+
+#[test]
+fn timestamps_are_formatted_as_utc() {
+    assert_eq!(audit::format_time(0), "1970-01-01 00:00:00");
+    assert_eq!(audit::format_time(951_782_400), "2000-02-29 00:00:00");
+    assert_eq!(audit::format_time(1_700_000_000), "2023-11-14 22:13:20");
+}
+
+#[test]
+fn events_survive_the_log_format() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("log");
+    let event = AuditEvent::new(
+        Action::Clone,
+        "a,b",
+        Some(Path::new("/src/x,y")),
+        "https://h/r.git",
+    );
+    audit::append(&file, &event).unwrap();
+    audit::append(&file, &AuditEvent::new(Action::Delete, "c", None, "")).unwrap();
+    let events = audit::load(&file);
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0], event);
+    assert_eq!(events[1].repo, None);
+}
+
+#[test]
+fn profile_changes_and_reveals_are_logged_and_filtered() {
+    let (_dir, mut app, _) = test_app("work,jdoe,j@doe.dev,tok,\n");
+    app.alias_input.set("home");
+    app.store_entries();
+    app.selected_index = Some(0);
+    app.edit_selected_entry();
+    app.alias_input.set("job");
+    app.store_entries();
+    app.selected_index = Some(1);
+    app.delete_current_entry();
+    list_command(&mut app, true, true, &mut Vec::new()).unwrap();
+
+    app.open_audit_log();
+    let actions: Vec<(Action, &str)> = app
+        .filtered_audit()
+        .iter()
+        .map(|event| (event.action, event.alias.as_str()))
+        .collect();
+    assert_eq!(
+        actions,
+        [
+            (Action::Reveal, "job"),
+            (Action::Delete, "home"),
+            (Action::Edit, "job"),
+            (Action::Create, "home"),
+        ]
+    );
+    assert!(app.filtered_audit()[2].detail.contains("renamed from work"));
+
+    app.audit_filter_input.set("delete");
+    app.refilter_audit();
+    assert_eq!(app.filtered_audit().len(), 1);
+    assert_eq!(app.audit_selected, Some(0));
+}
+
+#[test]
+fn revealing_a_token_in_the_preview_is_logged() {
+    let (_dir, mut app, _) = test_app("work,jdoe,j@doe.dev,tok,\nhome,jd,jd@home,,\n");
+    app.selected_index = Some(0);
+    assert!(!app.str_from_entry().contains("tok"));
+    press(&mut app, KeyCode::Char('v'));
+    assert!(app.str_from_entry().contains("Token: tok"));
+    press(&mut app, KeyCode::Char('v'));
+    assert!(!app.str_from_entry().contains("tok"));
+
+    // other profiles stay hidden, revealing an empty token logs nothing
+    press(&mut app, KeyCode::Char('v'));
+    press(&mut app, KeyCode::Char('j'));
+    assert!(!app.str_from_entry().contains("tok"));
+    press(&mut app, KeyCode::Char('v'));
+
+    let events = audit::load(&app.audit_file);
+    assert_eq!(events.len(), 2);
+    assert!(events.iter().all(|event| event.action == Action::Reveal
+        && event.alias == "work"
+        && event.detail == "preview"));
+}
+
+#[test]
+fn overrides_restores_and_imports_are_logged() {
+    let (_dir, mut app, _) = test_app("work,jdoe,j@doe.dev,tok,\n");
+    app.selected_index = Some(0);
+    app.config_key_input.set("http.extraHeader");
+    app.config_value_input.set("Authorization: secret");
+    app.add_config_override();
+    app.config_selected = Some(0);
+    app.remove_selected_config_override();
+    press(&mut app, KeyCode::Char('d'));
+    press(&mut app, KeyCode::Char('y'));
+    press(&mut app, KeyCode::Char('u'));
+
+    let events = audit::load(&app.audit_file);
+    let actions: Vec<(Action, &str)> = events
+        .iter()
+        .map(|event| (event.action, event.detail.as_str()))
+        .collect();
+    assert_eq!(
+        actions[..2],
+        [
+            (Action::Edit, "set http.extraHeader"),
+            (Action::Edit, "unset http.extraHeader"),
+        ]
+    );
+    assert_eq!(events.last().unwrap().action, Action::Restore);
+    assert!(events.iter().all(|event| !event.detail.contains("secret")));
+}
@@ -0,0 +1,159 @@
+mod common;
+
+use common::{entry, test_app};
+use gat::app::CurrentScreen;
+use gat::bundle::{self, Resolution, TokenMode};
+use gat::cli::{self, Command};
+use gat::handler::{handle_key_press, handle_paste};
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::fs;
+use std::process::ExitCode;
+
+fn press(app: &mut gat::app::App, code: KeyCode) {
+    handle_key_press(KeyEvent::new(code, KeyModifiers::NONE), app);
+}
+
+#[test]
+fn bundles_round_trip_in_every_token_mode() {
+    let mut work = entry("work", "jdoe", "j@doe.dev", "tok", "John");
+    work.host = "github.com".to_string();
+    work.git_config = vec![("pull.rebase".to_string(), "true".to_string())];
+    let entries = vec![work, entry("home", "jd", "jd@home", "", "")];
+
+    let plain = bundle::export(&entries, &TokenMode::Plain).unwrap();
+    let read = bundle::read(&plain, None).unwrap();
+    assert_eq!(read[0].to_string(), entries[0].to_string());
+
+    let excluded = bundle::export(&entries, &TokenMode::Excluded).unwrap();
+    assert!(!excluded.contains(",tok,"));
+    assert_eq!(bundle::read(&excluded, None).unwrap()[0].pa_token, "");
+
+    let encrypted = bundle::export(&entries, &TokenMode::Encrypted("secret".to_string())).unwrap();
+    assert!(bundle::is_encrypted(&encrypted));
+    assert!(!encrypted.contains(",tok,"));
+    assert!(bundle::read(&encrypted, None).is_err());
+    assert_eq!(
+        bundle::read(&encrypted, Some("wrong")).err().as_deref(),
+        Some("Wrong passphrase")
+    );
+    let read = bundle::read(&encrypted, Some("secret")).unwrap();
+    assert_eq!(read[0].to_string(), entries[0].to_string());
+    assert_eq!(read[1].pa_token, "");
+
+    assert!(bundle::read("work,jdoe,j@doe.dev,tok,\n", None).is_err());
+}
+
+#[test]
+fn conflicts_are_resolved_as_chosen() {
+    let local = vec![
+        entry("work", "jdoe", "j@doe.dev", "tok", ""),
+        entry("home", "jd", "jd@home", "tok2", ""),
+    ];
+    let bundled = vec![
+        // same profile without its token
+        entry("work", "jdoe", "j@doe.dev", "", ""),
+        entry("home", "jd", "jd@new.home", "", ""),
+        entry("oss", "jd", "jd@oss", "tok3", ""),
+    ];
+    let incoming = bundle::plan_merge(&local, bundled.clone(), None);
+    assert_eq!(
+        incoming
+            .iter()
+            .map(|incoming| incoming.conflict)
+            .collect::<Vec<_>>(),
+        [false, true, false]
+    );
+
+    let mut overwritten = local.clone();
+    let incoming = bundle::plan_merge(&local, bundled.clone(), Some(Resolution::Overwrite));
+    let summary = bundle::merge(&mut overwritten, incoming);
+    assert_eq!(
+        summary.to_string(),
+        "1 added, 1 overwritten, 0 renamed, 1 skipped"
+    );
+    assert_eq!(overwritten[1].email, "jd@new.home");
+    // the bundle had no token, the local one is kept
+    assert_eq!(overwritten[1].pa_token, "tok2");
+
+    let mut renamed = local.clone();
+    let incoming = bundle::plan_merge(&local, bundled, Some(Resolution::Rename));
+    bundle::merge(&mut renamed, incoming);
+    assert_eq!(renamed[1].email, "jd@home");
+    assert_eq!(renamed[2].alias, "home-2");
+}
+
+#[test]
+fn tui_import_asks_for_the_passphrase_and_conflict_handling() {
+    let (dir, mut app, _git) = test_app("work,jdoe,j@doe.dev,tok,\n");
+    let path = dir.path().join("profiles.gatbundle");
+    let bundled = vec![
+        entry("work", "jdoe", "j@corp.dev", "newtok", ""),
+        entry("oss", "jd", "jd@oss", "tok3", ""),
+    ];
+    fs::write(
+        &path,
+        bundle::export(&bundled, &TokenMode::Encrypted("secret".to_string())).unwrap(),
+    )
+    .unwrap();
+
+    press(&mut app, KeyCode::Char('b'));
+    assert!(app.current_screen == CurrentScreen::Bundle);
+    handle_paste(&path.to_string_lossy(), &mut app);
+    press(&mut app, KeyCode::Enter);
+    assert!(app.bundle_asks_passphrase);
+    handle_paste("secret", &mut app);
+    press(&mut app, KeyCode::Enter);
+    let incoming = app.bundle_incoming.as_ref().unwrap();
+    assert!(incoming[0].conflict && incoming[0].resolution == Resolution::Skip);
+
+    press(&mut app, KeyCode::Tab);
+    press(&mut app, KeyCode::Enter);
+    assert!(app.current_screen == CurrentScreen::Main);
+    assert_eq!(app.entries.len(), 2);
+    assert_eq!(app.entries[0].email, "j@corp.dev");
+    assert_eq!(app.entries[0].pa_token, "newtok");
+    assert_eq!(
+        app.status.as_deref(),
+        Some("Bundle imported: 1 added, 1 overwritten, 0 renamed, 0 skipped")
+    );
+    assert!(!app.dirty);
+}
+
+#[test]
+fn cli_exports_are_private_and_imports_compare_own_profiles() {
+    let (dir, mut app, _git) = test_app("work,jdoe,j@doe.dev,tok,,used=100\n");
+    let team = dir.path().join("team");
+    fs::write(&team, "ci,bot,ci@team,t3,\n").unwrap();
+    app.load_shared_stores(std::slice::from_ref(&team));
+
+    let path = dir.path().join("profiles.gatbundle");
+    let export = Command::Export {
+        file: Some(path.clone()),
+        aliases: vec!["work".to_string()],
+        tokens: "plain".to_string(),
+    };
+    assert_eq!(cli::run(export, &mut app), ExitCode::SUCCESS);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    // the same profile used at another time, and one shadowing a shared profile
+    let bundled = vec![
+        entry("work", "jdoe", "j@doe.dev", "tok", ""),
+        entry("ci", "bot", "ci@own", "t4", ""),
+    ];
+    fs::write(&path, bundle::export(&bundled, &TokenMode::Plain).unwrap()).unwrap();
+    let import = Command::Import {
+        file: path,
+        on_conflict: None,
+    };
+    assert_eq!(cli::run(import, &mut app), ExitCode::SUCCESS);
+    assert_eq!(
+        fs::read_to_string(&app.save_file).unwrap(),
+        "work,jdoe,j@doe.dev,tok,,used=100\nci,bot,ci@own,t4,\n"
+    );
+    assert_eq!(fs::read_to_string(&team).unwrap(), "ci,bot,ci@team,t3,\n");
+}
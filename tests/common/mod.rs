@@ -0,0 +1,115 @@
+#![allow(dead_code)]
+
+use gat::app::{App, Entry};
+use gat::git::{GitOutput, GitRunner};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tempfile::TempDir;
+
+/*
+* Records every git invocation instead of running it. Responses can be scripted
+* per argument prefix, everything else succeeds with empty output.
+*/
+#[derive(Default)]
+pub struct FakeGit {
+    pub calls: Mutex<Vec<(PathBuf, Vec<String>)>>,
+    pub responses: Mutex<Vec<(Vec<String>, GitOutput)>>,
+}
+
+impl FakeGit {
+    pub fn respond(&self, args_prefix: &[&str], success: bool, stdout: &str) {
+        self.responses.lock().unwrap().push((
+            args_prefix.iter().map(|arg| arg.to_string()).collect(),
+            GitOutput {
+                success,
+                stdout: stdout.to_string(),
+                stderr: String::new(),
+            },
+        ));
+    }
+
+    pub fn calls(&self) -> Vec<(PathBuf, Vec<String>)> {
+        self.calls.lock().unwrap().clone()
+    }
+}
+
+impl GitRunner for FakeGit {
+    fn run(&self, workdir: &Path, args: &[&str]) -> io::Result<GitOutput> {
+        let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        self.calls
+            .lock()
+            .unwrap()
+            .push((workdir.to_path_buf(), args.clone()));
+        for (prefix, output) in self.responses.lock().unwrap().iter() {
+            if args.starts_with(prefix) {
+                return Ok(GitOutput {
+                    success: output.success,
+                    stdout: output.stdout.clone(),
+                    stderr: output.stderr.clone(),
+                });
+            }
+        }
+        Ok(GitOutput {
+            success: true,
+            stdout: String::new(),
+            stderr: String::new(),
+        })
+    }
+}
+
+pub fn entry(alias: &str, username: &str, email: &str, token: &str, nickname: &str) -> Entry {
+    Entry {
+        alias: alias.to_string(),
+        username: username.to_string(),
+        email: email.to_string(),
+        pa_token: token.to_string(),
+        nickname: nickname.to_string(),
+        ..Entry::default()
+    }
+}
+
+/*
+* An app with a store and workdir inside a fresh temp dir and a fake git runner.
+*/
+pub fn test_app(store: &str) -> (TempDir, App, Arc<FakeGit>) {
+    let dir = tempfile::tempdir().unwrap();
+    let save_file = dir.path().join(".gat");
+    std::fs::write(&save_file, store).unwrap();
+    let workdir = dir.path().join("repo");
+    std::fs::create_dir(&workdir).unwrap();
+    let mut app = App::with_paths(dir.path().to_path_buf(), save_file, workdir);
+    let git = Arc::new(FakeGit::default());
+    app.git = git.clone();
+    (dir, app, git)
+}
+
+// tests using real git fail loudly where it is missing instead of passing unchecked
+pub fn git_init(workdir: &Path) {
+    let init = std::process::Command::new("git")
+        .args(["init", "-q"])
+        .current_dir(workdir)
+        .status()
+        .expect("git must be installed to run this test");
+    assert!(init.success(), "git init failed in {}", workdir.display());
+}
+
+pub fn local_config(workdir: &Path, key: &str) -> Option<String> {
+    let output = std::process::Command::new("git")
+        .args(["config", "--local", "--get", key])
+        .current_dir(workdir)
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+pub fn set_local(workdir: &Path, key: &str, value: &str) {
+    std::process::Command::new("git")
+        .args(["config", "--local", key, value])
+        .current_dir(workdir)
+        .status()
+        .unwrap();
+}
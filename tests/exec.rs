@@ -0,0 +1,90 @@
+mod common;
+
+use common::{entry, git_init, local_config};
+use gat::audit::{self, Action};
+use gat::exec::environment;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+fn var<'a>(vars: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    vars.iter()
+        .find(|(key, _)| key == name)
+        .map(|(_, value)| value.as_str())
+}
+
+#[test]
+fn the_environment_carries_identity_and_helper() {
+    let mut work = entry("work", "jdoe", "j@doe.dev", "tok", "John Doe");
+    work.host = "github.com".to_string();
+    let vars = environment(&work, Some("!gat credential work".to_string()));
+    assert_eq!(var(&vars, "GIT_AUTHOR_NAME"), Some("John Doe"));
+    assert_eq!(var(&vars, "GIT_COMMITTER_EMAIL"), Some("j@doe.dev"));
+    assert_eq!(
+        var(&vars, "GIT_CONFIG_KEY_0"),
+        Some("credential.https://github.com.helper")
+    );
+    assert_eq!(var(&vars, "GIT_CONFIG_VALUE_0"), Some(""));
+    assert_eq!(
+        var(&vars, "GIT_CONFIG_VALUE_1"),
+        Some("!gat credential work")
+    );
+
+    // without a host the token would go to any server, so there is no helper
+    work.host.clear();
+    let vars = environment(&work, Some("!gat credential work".to_string()));
+    assert_eq!(var(&vars, "GIT_CONFIG_COUNT"), None);
+}
+
+#[test]
+fn exec_commits_and_authenticates_without_writing_config() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = dir.path().join("store");
+    std::fs::write(&store, "work,jdoe,j@doe.dev,tok,John Doe,host=github.com\n").unwrap();
+    let repo = dir.path().join("repo");
+    std::fs::create_dir(&repo).unwrap();
+    git_init(&repo);
+    let gat = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_gat"))
+            .arg("--store")
+            .arg(&store)
+            .arg("-C")
+            .arg(&repo)
+            .args(["exec", "work", "--"])
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap()
+    };
+
+    let commit = gat(&["git", "commit", "-q", "--allow-empty", "-m", "one-off"]);
+    assert!(commit.wait_with_output().unwrap().status.success());
+    let log = gat(&["git", "log", "-1", "--format=%an <%ae> %cn"]);
+    let log = String::from_utf8(log.wait_with_output().unwrap().stdout).unwrap();
+    assert_eq!(log.trim(), "John Doe <j@doe.dev> John Doe");
+    assert_eq!(local_config(&repo, "user.email"), None);
+
+    let mut fill = gat(&["git", "credential", "fill"]);
+    fill.stdin
+        .take()
+        .unwrap()
+        .write_all(b"protocol=https\nhost=github.com\n\n")
+        .unwrap();
+    let answer = String::from_utf8(fill.wait_with_output().unwrap().stdout).unwrap();
+    assert!(answer.contains("password=tok"));
+
+    let failing = gat(&["git", "rev-parse", "--verify", "nope"]);
+    assert_eq!(failing.wait_with_output().unwrap().status.code(), Some(128));
+
+    // arguments may carry secrets, only the program is logged
+    let execs: Vec<_> = audit::load(&audit::audit_file(&store))
+        .into_iter()
+        .filter(|event| event.action == Action::Exec)
+        .collect();
+    assert_eq!(execs.len(), 4);
+    assert!(execs.iter().all(|event| event.detail == "git"));
+
+    let killed = gat(&["sh", "-c", "kill -TERM $$"]);
+    assert_eq!(killed.wait_with_output().unwrap().status.code(), Some(143));
+}
@@ -0,0 +1,371 @@
+mod common;
+
+use common::{git_init, local_config, set_local, test_app, FakeGit};
+use gat::audit::{self, Action};
+use gat::git::SystemGit;
+use gat::inject::{self, InjectScope};
+use std::path::Path;
+use std::sync::Arc;
+
+fn script_repo(git: &FakeGit, workdir: &Path) {
+    let git_dir = workdir.join(".git");
+    for name in ["config", "config.worktree"] {
+        git.respond(
+            &["rev-parse", "--path-format=absolute", "--git-path", name],
+            true,
+            &format!("{}\n", git_dir.join(name).display()),
+        );
+    }
+    git.respond(
+        &["rev-parse", "--show-toplevel"],
+        true,
+        &format!("{}\n", workdir.display()),
+    );
+}
+
+fn config_call(file: &Path, key: &str, value: &str) -> Vec<String> {
+    vec![
+        "config".to_string(),
+        "--file".to_string(),
+        file.display().to_string(),
+        "--replace-all".to_string(),
+        key.to_string(),
+        value.to_string(),
+    ]
+}
+
+#[test]
+fn injection_sets_nickname_and_email_in_the_workdir() {
+    let (_dir, mut app, git) = test_app("work,jdoe,j@doe.dev,tok,John Doe\n");
+    script_repo(&git, &app.workdir);
+    app.selected_index = Some(0);
+    app.inject_selected_profile();
+
+    let calls = git.calls();
+    let local = app.workdir.join(".git/config");
+    assert!(calls.iter().all(|(workdir, _)| *workdir == app.workdir));
+    let args: Vec<Vec<String>> = calls.into_iter().map(|(_, args)| args).collect();
+    assert!(args.contains(&config_call(&local, "user.name", "John Doe")));
+    assert!(args.contains(&config_call(&local, "user.email", "j@doe.dev")));
+}
+
+#[test]
+fn injection_falls_back_to_the_username() {
+    let (_dir, mut app, git) = test_app("work,jdoe,j@doe.dev,tok,\n");
+    script_repo(&git, &app.workdir);
+    app.selected_index = Some(0);
+    app.inject_selected_profile();
+    let local = app.workdir.join(".git/config");
+    assert!(git
+        .calls()
+        .iter()
+        .any(|(_, args)| *args == config_call(&local, "user.name", "jdoe")));
+}
+
+#[test]
+fn injection_outside_a_repository_reports_and_changes_nothing() {
+    let (_dir, mut app, git) = test_app("work,jdoe,j@doe.dev,tok,\n");
+    app.selected_index = Some(0);
+    app.inject_selected_profile();
+    assert_eq!(git.calls().len(), 1);
+    assert!(app.status.is_some());
+}
+
+#[test]
+fn worktree_scope_enables_the_extension_first() {
+    let (_dir, mut app, git) = test_app("work,jdoe,j@doe.dev,tok,\n");
+    script_repo(&git, &app.workdir);
+    app.selected_index = Some(0);
+    app.inject_scope = InjectScope::Worktree;
+    app.refresh_injection_plan();
+
+    let git_dir = app.workdir.join(".git");
+    let changes = app.injection_plan.unwrap();
+    assert_eq!(changes.len(), 3);
+    assert_eq!(changes[0].file, git_dir.join("config"));
+    assert_eq!(changes[0].key, "extensions.worktreeConfig");
+    assert_eq!(changes[1].file, git_dir.join("config.worktree"));
+    assert_eq!(changes[2].file, git_dir.join("config.worktree"));
+}
+
+#[test]
+fn global_and_include_scopes_write_to_the_home_directory() {
+    let (dir, mut app, git) = test_app("work,jdoe,j@doe.dev,tok,\n");
+    script_repo(&git, &app.workdir);
+    app.selected_index = Some(0);
+    app.inject_scope = InjectScope::Global;
+    app.refresh_injection_plan();
+    let changes = app.injection_plan.clone().unwrap();
+    assert!(changes
+        .iter()
+        .all(|change| change.file == dir.path().join(".gitconfig")));
+
+    app.cycle_inject_scope();
+    assert!(app.inject_scope == InjectScope::Include);
+    let changes = app.injection_plan.unwrap();
+    let include = inject::include_file(dir.path(), "work");
+    assert_eq!(changes[0].file, dir.path().join(".gitconfig"));
+    assert_eq!(
+        changes[0].key,
+        format!("includeIf.gitdir:{}/.path", app.workdir.display())
+    );
+    assert_eq!(changes[0].value, include.display().to_string());
+    assert_eq!(changes[1].file, include);
+}
+
+#[test]
+fn include_files_stay_in_their_directory() {
+    let home = Path::new("/home/me");
+    let include = inject::include_file(home, "../../.bashrc");
+    assert_eq!(include.parent(), Some(home.join(".gat.d").as_path()));
+    assert_ne!(
+        inject::include_file(home, "a/b"),
+        inject::include_file(home, "a%2Fb")
+    );
+    assert_eq!(
+        inject::include_file(home, "work"),
+        home.join(".gat.d/work.gitconfig")
+    );
+}
+
+#[test]
+fn include_scope_applies_to_a_real_repository() {
+    let (dir, mut app, _git) = test_app("work,jdoe,j@doe.dev,tok,\n");
+    app.git = Arc::new(SystemGit);
+    git_init(&app.workdir);
+    app.selected_index = Some(0);
+    app.inject_scope = InjectScope::Include;
+    app.inject_selected_profile();
+    assert!(app.status.is_none());
+
+    let include = std::fs::read_to_string(inject::include_file(dir.path(), "work")).unwrap();
+    assert!(include.contains("email = j@doe.dev"));
+    let global = std::fs::read_to_string(dir.path().join(".gitconfig")).unwrap();
+    assert!(global.contains("[includeIf \"gitdir:"));
+}
+
+#[test]
+fn clone_asks_the_helper_for_the_token_and_injects_into_the_new_repo() {
+    let (_dir, mut app, git) = test_app("work,jdoe,j@doe.dev,tok,\n");
+    script_repo(&git, &app.workdir.join("project"));
+    app.selected_index = Some(0);
+    app.clone_url_input
+        .insert_str("https://github.com/owner/project.git");
+    app.clone_repo();
+
+    let calls = git.calls();
+    assert_eq!(calls[0].0, app.workdir);
+    let clone = &calls[0].1;
+    assert_eq!(
+        clone[..3],
+        ["-c", "credential.https://github.com.helper=", "-c"].map(String::from)
+    );
+    assert!(clone[3].ends_with(" credential --host 'github.com' 'work'"));
+    assert_eq!(
+        clone[4..],
+        ["clone", "https://github.com/owner/project.git"].map(String::from)
+    );
+    assert!(calls
+        .iter()
+        .flat_map(|(_, args)| args)
+        .all(|arg| !arg.contains("tok")));
+    // later fetches and pushes ask the helper as well
+    let helper = clone[3].trim_start_matches("credential.https://github.com.helper=");
+    assert!(calls.contains(&(
+        app.workdir.join("project"),
+        [
+            "config",
+            "--local",
+            "--replace-all",
+            "credential.https://github.com.helper",
+            helper
+        ]
+        .map(String::from)
+        .to_vec()
+    )));
+    let local = app.workdir.join("project/.git/config");
+    assert!(calls[1..]
+        .iter()
+        .all(|(workdir, _)| *workdir == app.workdir.join("project")));
+    let args: Vec<Vec<String>> = calls.into_iter().map(|(_, args)| args).collect();
+    assert!(args.contains(&config_call(&local, "user.name", "jdoe")));
+    assert!(args.contains(&config_call(&local, "user.email", "j@doe.dev")));
+}
+
+#[test]
+fn failed_clone_skips_injection_and_reports() {
+    let (_dir, mut app, git) = test_app("work,jdoe,j@doe.dev,tok,\n");
+    git.respond(&["-c"], false, "");
+    app.selected_index = Some(0);
+    app.clone_url_input
+        .insert_str("https://github.com/owner/project.git");
+    app.clone_repo();
+
+    assert_eq!(git.calls().len(), 1);
+    assert!(app.status.is_some());
+}
+
+#[test]
+fn overrides_are_injected_and_removed_again() {
+    let (_dir, mut app, _git) =
+        test_app("work,jdoe,j@doe.dev,tok,,cfg.pull.rebase=true,cfg.core.autocrlf=input\n");
+    app.git = Arc::new(SystemGit);
+    git_init(&app.workdir);
+    std::process::Command::new("git")
+        .args(["config", "--local", "core.bare", "false"])
+        .current_dir(&app.workdir)
+        .status()
+        .unwrap();
+    app.selected_index = Some(0);
+    app.inject_selected_profile();
+    assert_eq!(
+        local_config(&app.workdir, "pull.rebase").as_deref(),
+        Some("true")
+    );
+    assert_eq!(
+        local_config(&app.workdir, "core.autocrlf").as_deref(),
+        Some("input")
+    );
+
+    app.uninject();
+    assert!(app.status.is_none());
+    assert_eq!(local_config(&app.workdir, "user.email"), None);
+    assert_eq!(local_config(&app.workdir, "pull.rebase"), None);
+    assert_eq!(local_config(&app.workdir, "core.autocrlf"), None);
+    assert_eq!(
+        local_config(&app.workdir, "core.bare").as_deref(),
+        Some("false")
+    );
+    let removal = audit::load(&app.audit_file).pop().unwrap();
+    assert_eq!(removal.action, Action::Remove);
+    assert_eq!(removal.alias, "work");
+    assert_eq!(removal.repo.as_deref(), Some(app.workdir.as_path()));
+}
+
+#[test]
+fn switching_profiles_drops_the_overrides_of_the_previous_one() {
+    let (_dir, mut app, _git) = test_app(concat!(
+        "work,jdoe,j@doe.dev,tok,,cfg.http.proxy=http://proxy:3128,",
+        "cfg.url.https://mirror.example/.insteadOf=https://github.com/\n",
+        "home,jd,jd@home,tok,,cfg.core.autocrlf=input\n",
+    ));
+    app.git = Arc::new(SystemGit);
+    git_init(&app.workdir);
+    // a multi-valued key gat has to replace as a whole
+    for base in ["https://gitlab.com/", "https://bitbucket.org/"] {
+        std::process::Command::new("git")
+            .args([
+                "config",
+                "--add",
+                "url.https://mirror.example/.insteadOf",
+                base,
+            ])
+            .current_dir(&app.workdir)
+            .status()
+            .unwrap();
+    }
+    app.selected_index = Some(0);
+    app.inject_selected_profile();
+    assert!(app.status.is_none());
+    assert_eq!(
+        local_config(&app.workdir, "url.https://mirror.example/.insteadOf").as_deref(),
+        Some("https://github.com/")
+    );
+
+    app.selected_index = Some(1);
+    app.inject_selected_profile();
+    assert!(app.status.is_none());
+    assert_eq!(local_config(&app.workdir, "http.proxy"), None);
+    assert_ne!(
+        local_config(&app.workdir, "url.https://mirror.example/.insteadOf").as_deref(),
+        Some("https://github.com/")
+    );
+    assert_eq!(
+        local_config(&app.workdir, "core.autocrlf").as_deref(),
+        Some("input")
+    );
+    assert_eq!(
+        local_config(&app.workdir, "gat.profile").as_deref(),
+        Some("home")
+    );
+    let config = std::fs::read_to_string(app.workdir.join(".git/config")).unwrap();
+    assert!(!config.contains("[gat \"http.proxy\"]"));
+}
+
+#[test]
+fn uninject_restores_values_from_before_the_first_injection() {
+    let (_dir, mut app, _git) = test_app("work,jdoe,j@doe.dev,tok,\nhome,jd,jd@home,tok,\n");
+    app.git = Arc::new(SystemGit);
+    git_init(&app.workdir);
+    set_local(&app.workdir, "user.name", "Original");
+    app.selected_index = Some(0);
+    app.inject_selected_profile();
+    app.selected_index = Some(1);
+    app.inject_selected_profile();
+    assert_eq!(
+        local_config(&app.workdir, "user.name").as_deref(),
+        Some("jd")
+    );
+    assert_eq!(
+        local_config(&app.workdir, "gat.profile").as_deref(),
+        Some("home")
+    );
+
+    app.refresh_uninject_plan();
+    let restores = app.uninject_plan.clone().unwrap();
+    assert_eq!(restores.len(), 2);
+    assert!(restores.iter().any(
+        |restore| restore.key == "user.name" && restore.previous.as_deref() == Some("Original")
+    ));
+
+    app.uninject();
+    assert!(app.status.is_none());
+    assert_eq!(
+        local_config(&app.workdir, "user.name").as_deref(),
+        Some("Original")
+    );
+    assert_eq!(local_config(&app.workdir, "user.email"), None);
+    assert_eq!(local_config(&app.workdir, "gat.profile"), None);
+    let config = std::fs::read_to_string(app.workdir.join(".git/config")).unwrap();
+    assert!(!config.contains("[gat"));
+}
+
+#[test]
+fn uninject_keeps_keys_changed_after_injection() {
+    let (_dir, mut app, _git) = test_app("work,jdoe,j@doe.dev,tok,\n");
+    app.git = Arc::new(SystemGit);
+    git_init(&app.workdir);
+    app.selected_index = Some(0);
+    app.inject_selected_profile();
+    set_local(&app.workdir, "user.email", "manual@edit");
+
+    app.uninject();
+    assert_eq!(local_config(&app.workdir, "user.name"), None);
+    assert_eq!(
+        local_config(&app.workdir, "user.email").as_deref(),
+        Some("manual@edit")
+    );
+}
+
+#[test]
+fn uninject_of_an_include_only_drops_this_repos_include() {
+    let (dir, mut app, _git) = test_app("work,jdoe,j@doe.dev,tok,\n");
+    app.git = Arc::new(SystemGit);
+    git_init(&app.workdir);
+    app.selected_index = Some(0);
+    app.inject_scope = InjectScope::Global;
+    app.inject_selected_profile();
+    app.inject_scope = InjectScope::Include;
+    app.inject_selected_profile();
+
+    app.uninject();
+    let global = std::fs::read_to_string(dir.path().join(".gitconfig")).unwrap();
+    assert!(!global.contains("includeIf"));
+    assert!(global.contains("email = j@doe.dev"));
+    let removal = audit::load(&app.audit_file).pop().unwrap();
+    assert_eq!(
+        (removal.action, removal.alias.as_str()),
+        (Action::Remove, "work")
+    );
+    assert_eq!(removal.detail, InjectScope::Include.label());
+}
@@ -0,0 +1,47 @@
+mod common;
+
+use common::test_app;
+use gat::cli::{list_command, show_command};
+use serde_json::Value;
+
+const STORE: &str =
+    "work,jdoe,j@doe.dev,ghp_secret,John Doe,host=github.com,cfg.pull.rebase=true\nbare,b,b@x,,\n";
+
+fn json(output: Vec<u8>) -> Value {
+    serde_json::from_slice(&output).unwrap()
+}
+
+#[test]
+fn list_redacts_tokens_unless_asked() {
+    let (_dir, mut app, _) = test_app(STORE);
+    let mut out = Vec::new();
+    list_command(&mut app, true, false, &mut out).unwrap();
+    assert!(!String::from_utf8_lossy(&out).contains("ghp_secret"));
+    let list = json(out);
+    assert_eq!(list["version"], 1);
+    assert_eq!(list["profiles"][0]["alias"], "work");
+    assert_eq!(list["profiles"][0]["author_name"], "John Doe");
+    assert_eq!(list["profiles"][0]["has_token"], true);
+    assert_eq!(list["profiles"][0]["token"], Value::Null);
+    assert_eq!(list["profiles"][0]["git_config"][0]["key"], "pull.rebase");
+    assert_eq!(list["profiles"][1]["has_token"], false);
+
+    let mut out = Vec::new();
+    list_command(&mut app, true, true, &mut out).unwrap();
+    assert_eq!(json(out)["profiles"][0]["token"], "ghp_secret");
+}
+
+#[test]
+fn show_prints_one_profile() {
+    let (_dir, mut app, _) = test_app(STORE);
+    let mut out = Vec::new();
+    show_command(&mut app, "work", true, false, &mut out).unwrap();
+    let shown = json(out);
+    assert_eq!(shown["version"], 1);
+    assert_eq!(shown["profile"]["host"], "github.com");
+
+    let mut out = Vec::new();
+    show_command(&mut app, "work", false, false, &mut out).unwrap();
+    assert!(String::from_utf8(out).unwrap().contains("token: ***"));
+    assert!(show_command(&mut app, "nope", true, false, &mut Vec::new()).is_err());
+}
@@ -0,0 +1,89 @@
+mod common;
+
+use common::test_app;
+use gat::app::{App, CurrentScreen, CurrentlyEditing};
+use gat::handler::handle_mouse;
+use gat::ui::ui;
+use ratatui::crossterm::event::{KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use ratatui::{backend::TestBackend, Terminal};
+
+const STORE: &str =
+    "work,jdoe,j@doe.dev,tok,\nhome,jd,jd@home.org,tok,\nschool,st,st@uni.edu,tok,\n";
+
+// draw the app and return where the text appears on screen
+fn locate(app: &App, text: &str) -> (u16, u16) {
+    let mut terminal = Terminal::new(TestBackend::new(100, 30)).unwrap();
+    terminal.draw(|f| ui(f, app)).unwrap();
+    let buffer = terminal.backend().buffer();
+    for row in 0..buffer.area.height {
+        let line: String = (0..buffer.area.width)
+            .map(|column| buffer[(column, row)].symbol())
+            .collect();
+        if let Some(idx) = line.find(text) {
+            return (line[..idx].chars().count() as u16, row);
+        }
+    }
+    panic!("{} is not on screen", text);
+}
+
+fn mouse(app: &mut App, kind: MouseEventKind, (column, row): (u16, u16)) {
+    handle_mouse(
+        MouseEvent {
+            kind,
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        },
+        app,
+    );
+}
+
+fn click(app: &mut App, at: (u16, u16)) {
+    mouse(app, MouseEventKind::Down(MouseButton::Left), at);
+}
+
+#[test]
+fn clicks_select_and_double_clicks_open_the_inject_prompt() {
+    let (_dir, mut app, _git) = test_app(STORE);
+    app.selected_index = Some(0);
+    let school = locate(&app, "school");
+    click(&mut app, school);
+    assert_eq!(app.selected_index, Some(2));
+    assert!(app.current_screen == CurrentScreen::Main);
+
+    click(&mut app, school);
+    assert!(app.current_screen == CurrentScreen::Injecting);
+
+    // the No button answers like the n key
+    let no = locate(&app, "n: No");
+    click(&mut app, no);
+    assert!(app.current_screen == CurrentScreen::Main);
+}
+
+#[test]
+fn the_wheel_scrolls_the_list() {
+    let (_dir, mut app, _git) = test_app(STORE);
+    app.selected_index = Some(0);
+    mouse(&mut app, MouseEventKind::ScrollDown, (0, 0));
+    mouse(&mut app, MouseEventKind::ScrollDown, (0, 0));
+    assert_eq!(app.selected_index, Some(2));
+    mouse(&mut app, MouseEventKind::ScrollUp, (0, 0));
+    assert_eq!(app.selected_index, Some(1));
+}
+
+#[test]
+fn clicking_an_editor_field_focuses_it() {
+    let (_dir, mut app, _git) = test_app(STORE);
+    app.current_screen = CurrentScreen::Editing;
+    app.currently_editing = Some(CurrentlyEditing::Alias);
+    let host = locate(&app, "Host, e.g.");
+    // into the input below the title
+    click(&mut app, (host.0, host.1 + 1));
+    assert_eq!(app.currently_editing, Some(CurrentlyEditing::Host));
+
+    // the list below the popup does not react
+    app.selected_index = Some(0);
+    let school = locate(&app, "school");
+    click(&mut app, school);
+    assert_eq!(app.selected_index, Some(0));
+}
@@ -0,0 +1,94 @@
+mod common;
+
+use common::test_app;
+use gat::app::{App, SortOrder};
+use gat::inject::InjectScope;
+use gat::store;
+use std::fs;
+
+fn aliases(app: &App) -> Vec<&str> {
+    app.entries
+        .iter()
+        .map(|entry| entry.alias.as_str())
+        .collect()
+}
+
+fn stored_aliases(app: &App) -> Vec<String> {
+    store::parse_entries(&fs::read_to_string(&app.save_file).unwrap())
+        .into_iter()
+        .map(|entry| entry.alias)
+        .collect()
+}
+
+#[test]
+fn moved_and_pinned_profiles_keep_their_place_in_the_store() {
+    let (_dir, mut app, _git) = test_app(
+        "b,u,b@x.dev,t,\n\
+         a,u,a@x.dev,t,\n\
+         c,u,c@x.dev,t,\n",
+    );
+    app.selected_index = Some(0);
+    app.move_selected(true);
+    assert_eq!(aliases(&app), ["a", "b", "c"]);
+    assert_eq!(app.selected_index, Some(1));
+
+    app.selected_index = Some(2);
+    app.toggle_pin();
+    assert_eq!(aliases(&app), ["c", "a", "b"]);
+    assert_eq!(app.selected_index, Some(0));
+    // pinned profiles cannot be moved below unpinned ones
+    app.move_selected(true);
+    assert_eq!(aliases(&app), ["c", "a", "b"]);
+    assert_eq!(stored_aliases(&app), ["c", "a", "b"]);
+    assert!(app.entries[0].pinned);
+
+    app.reload_store();
+    assert_eq!(aliases(&app), ["c", "a", "b"]);
+    assert!(app.entries[0].pinned);
+
+    app.selected_index = Some(0);
+    app.toggle_pin();
+    app.sort_entries(SortOrder::Alias);
+    assert_eq!(stored_aliases(&app), ["a", "b", "c"]);
+}
+
+#[test]
+fn sorting_by_last_used_and_host_keeps_pins_on_top() {
+    let (_dir, mut app, _git) = test_app(
+        "old,u,o@x.dev,t,,host=gitlab.com,used=100\n\
+         new,u,n@x.dev,t,,used=300\n\
+         fav,u,f@x.dev,t,,host=codeberg.org,pinned=1\n\
+         mid,u,m@x.dev,t,,host=github.com,used=200\n",
+    );
+    app.selected_index = Some(1);
+    app.sort_entries(SortOrder::LastUsed);
+    assert_eq!(aliases(&app), ["fav", "new", "mid", "old"]);
+    assert_eq!(app.selected_index, Some(1));
+
+    app.sort_entries(SortOrder::Host);
+    assert_eq!(aliases(&app), ["fav", "mid", "old", "new"]);
+}
+
+#[test]
+fn injections_record_when_a_profile_was_last_used() {
+    let (_dir, mut app, git) = test_app("work,jdoe,j@doe.dev,tok,\n");
+    let workdir = app.workdir.clone();
+    git.respond(
+        &[
+            "rev-parse",
+            "--path-format=absolute",
+            "--git-path",
+            "config",
+        ],
+        true,
+        &format!("{}\n", workdir.join(".git/config").display()),
+    );
+    assert!(app.inject_profile(0, InjectScope::Local, &workdir));
+    let last_used = app.entries[0].last_used;
+    assert!(last_used > 0);
+
+    let stored = store::parse_entries(&fs::read_to_string(&app.save_file).unwrap());
+    assert_eq!(stored[0].last_used, last_used);
+    // timestamps alone do not push out backups of real changes
+    assert!(!store::backup_file(&app.save_file, 1).exists());
+}
@@ -0,0 +1,64 @@
+mod common;
+
+use common::{git_init, set_local, test_app};
+use gat::git::SystemGit;
+use std::fs;
+use std::process::Command;
+use std::sync::Arc;
+
+fn git(workdir: &std::path::Path, args: &[&str]) -> String {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(workdir)
+        .output()
+        .unwrap();
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn commits_get_trailers_until_the_session_ends() {
+    let (_dir, mut app, _) =
+        test_app("work,jdoe,j@doe.dev,tok,John Doe\nbuddy,bud,bud@pair.dev,tok2,\n");
+    app.git = Arc::new(SystemGit);
+    let workdir = app.workdir.clone();
+    git_init(&workdir);
+    set_local(&workdir, "user.name", "John Doe");
+    set_local(&workdir, "user.email", "j@doe.dev");
+    app.set_workdir(&workdir).unwrap();
+
+    app.open_pairing();
+    app.pairing_selected = Some(1);
+    app.toggle_coauthor();
+    app.start_pairing();
+    assert_eq!(app.coauthors, ["Co-authored-by: bud <bud@pair.dev>"]);
+
+    git(&workdir, &["commit", "-q", "--allow-empty", "-m", "paired"]);
+    let message = git(&workdir, &["log", "-1", "--format=%B"]);
+    assert!(message.contains("Co-authored-by: bud <bud@pair.dev>"));
+
+    app.end_pairing();
+    assert!(app.coauthors.is_empty());
+    assert!(!workdir.join(".git/hooks/prepare-commit-msg").exists());
+    git(&workdir, &["commit", "-q", "--allow-empty", "-m", "alone"]);
+    let message = git(&workdir, &["log", "-1", "--format=%B"]);
+    assert!(!message.contains("Co-authored-by"));
+}
+
+#[test]
+fn foreign_hooks_are_left_alone() {
+    let (_dir, mut app, _) = test_app("buddy,bud,bud@pair.dev,tok2,\n");
+    app.git = Arc::new(SystemGit);
+    let workdir = app.workdir.clone();
+    git_init(&workdir);
+    app.set_workdir(&workdir).unwrap();
+    let hook = workdir.join(".git/hooks/prepare-commit-msg");
+    fs::write(&hook, "#!/bin/sh\necho mine\n").unwrap();
+
+    app.open_pairing();
+    app.toggle_coauthor();
+    app.start_pairing();
+    assert!(app.status.as_deref().unwrap().contains("already exists"));
+    assert!(app.coauthors.is_empty());
+    app.end_pairing();
+    assert_eq!(fs::read_to_string(&hook).unwrap(), "#!/bin/sh\necho mine\n");
+}
@@ -0,0 +1,85 @@
+mod common;
+
+use common::test_app;
+use gat::app::Entry;
+use gat::credential;
+use gat::provider::{parse_clone_url, Provider};
+
+#[test]
+fn clone_urls_keep_nested_groups_and_turn_ssh_into_https() {
+    let url = parse_clone_url("https://gitlab.com/group/sub/project").unwrap();
+    assert_eq!(url.host, "gitlab.com");
+    assert_eq!(url.path, "group/sub/project");
+    assert_eq!(url.name, "project");
+
+    let url = parse_clone_url("git@gitlab.example.com:group/sub/project.git").unwrap();
+    assert_eq!(
+        url.https(),
+        "https://gitlab.example.com/group/sub/project.git"
+    );
+    assert_eq!(url.name, "project");
+
+    let url = parse_clone_url("ssh://git@git.example.com:2222/team/tool.git/").unwrap();
+    assert_eq!(url.https(), "https://git.example.com/team/tool.git");
+
+    let url = parse_clone_url("https://me@dev.azure.com/org/proj/_git/repo").unwrap();
+    assert_eq!(url.https(), "https://dev.azure.com/org/proj/_git/repo");
+    assert_eq!(url.name, "repo");
+
+    for ssh in [
+        "git@ssh.dev.azure.com:v3/org/proj/repo",
+        "ssh://git@ssh.dev.azure.com:22/v3/org/proj/repo",
+    ] {
+        let url = parse_clone_url(ssh).unwrap();
+        assert_eq!(url.https(), "https://dev.azure.com/org/proj/_git/repo");
+        assert_eq!(url.name, "repo");
+    }
+    assert!(parse_clone_url("git@ssh.dev.azure.com:v3/org/repo").is_err());
+
+    assert!(parse_clone_url("owner/project").is_err());
+    assert!(parse_clone_url("https://github.com/").is_err());
+}
+
+#[test]
+fn gitlab_clones_present_the_token_as_oauth2() {
+    let (_dir, mut app, git) = test_app("work,jdoe,j@doe.dev,t@k,,host=gitlab.com\n");
+    app.selected_index = Some(0);
+    app.clone_url_input
+        .insert_str("git@gitlab.com:group/sub/project.git");
+    app.clone_repo();
+
+    let clone = &git.calls()[0].1;
+    assert_eq!(clone[5], "https://gitlab.com/group/sub/project.git");
+    assert!(clone[3].ends_with(" credential --host 'gitlab.com' 'work'"));
+    assert_eq!(
+        credential::respond(
+            Some(&app.entries[0]),
+            "get",
+            "protocol=https\nhost=gitlab.com\n",
+            Some("gitlab.com")
+        ),
+        "username=oauth2\npassword=t@k\n"
+    );
+}
+
+#[test]
+fn an_explicit_provider_round_trips_and_wins_over_the_host() {
+    let line = "work,jdoe,j@doe.dev,tok,,host=git.example.com,provider=gitlab";
+    let entry = Entry::from_line(line).unwrap();
+    assert_eq!(entry.provider, Some(Provider::GitLab));
+    assert_eq!(entry.to_string(), line);
+    assert_eq!(
+        credential::respond(
+            Some(&entry),
+            "get",
+            "protocol=https\nhost=git.example.com\n",
+            None
+        ),
+        "username=oauth2\npassword=tok\n"
+    );
+
+    let detected = Entry::from_line("work,jdoe,j@doe.dev,tok,,host=bitbucket.org").unwrap();
+    assert_eq!(detected.provider, None);
+    assert_eq!(Provider::of(&detected), Provider::Bitbucket);
+    assert_eq!(Provider::detect("git.example.com"), Provider::Gitea);
+}
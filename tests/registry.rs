@@ -0,0 +1,113 @@
+mod common;
+
+use common::test_app;
+use gat::app::CurrentScreen;
+use gat::handler::handle_key_press;
+use gat::inject::InjectScope;
+use gat::registry::{self, RepoRecord};
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::fs;
+
+fn press(app: &mut gat::app::App, code: KeyCode) {
+    handle_key_press(KeyEvent::new(code, KeyModifiers::NONE), app);
+}
+
+#[test]
+fn injection_records_the_repo_and_global_does_not() {
+    let (dir, mut app, git) = test_app("work,jdoe,j@doe.dev,tok,\n");
+    git.respond(
+        &[
+            "rev-parse",
+            "--path-format=absolute",
+            "--git-path",
+            "config",
+        ],
+        true,
+        &format!("{}\n", app.workdir.join(".git/config").display()),
+    );
+    app.selected_index = Some(0);
+    app.inject_selected_profile();
+    app.inject_scope = InjectScope::Global;
+    app.inject_selected_profile();
+
+    let repos = registry::load_registry(&registry::registry_file(&dir.path().join(".gat")));
+    assert_eq!(repos.len(), 1);
+    assert_eq!(repos[0].alias, "work");
+    assert!(repos[0].scope == InjectScope::Local);
+    assert_eq!(repos[0].path, fs::canonicalize(&app.workdir).unwrap());
+    assert!(app.str_from_entry().contains("Used in 1 repo:"));
+}
+
+#[test]
+fn records_of_vanished_repos_are_pruned_on_load() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join(".gat.repos");
+    let kept = dir.path().join("kept");
+    fs::create_dir(&kept).unwrap();
+    let record = |path| RepoRecord {
+        alias: "work".to_string(),
+        scope: InjectScope::Local,
+        path,
+        last_used: 1,
+    };
+    registry::save_registry(
+        &file,
+        &[record(kept.clone()), record(dir.path().join("gone"))],
+    )
+    .unwrap();
+
+    let repos = registry::load_registry(&file);
+    assert_eq!(repos.len(), 1);
+    assert_eq!(repos[0].path, kept);
+}
+
+#[test]
+fn editing_the_identity_offers_to_update_known_repos() {
+    let (_dir, mut app, git) = test_app("work,jdoe,j@doe.dev,tok,\n");
+    let other = app.workdir.parent().unwrap().join("other");
+    fs::create_dir(&other).unwrap();
+    registry::record(&mut app.repos, "work", InjectScope::Local, &app.workdir);
+    registry::record(&mut app.repos, "work", InjectScope::Local, &other);
+    app.selected_index = Some(0);
+
+    press(&mut app, KeyCode::Char('e'));
+    assert!(app.current_screen == CurrentScreen::Editing);
+    assert_eq!(app.alias_input.value(), "work");
+    for _ in 0..2 {
+        press(&mut app, KeyCode::Tab);
+    }
+    press(&mut app, KeyCode::Char('e'));
+    press(&mut app, KeyCode::Enter);
+    assert!(app.current_screen == CurrentScreen::Propagating);
+    assert_eq!(app.entries.len(), 1);
+    assert_eq!(app.entries[0].email, "j@doe.deve");
+
+    press(&mut app, KeyCode::Char('y'));
+    assert!(app.current_screen == CurrentScreen::Main);
+    let workdirs: Vec<_> = git
+        .calls()
+        .into_iter()
+        .map(|(workdir, _)| workdir)
+        .collect();
+    assert!(workdirs.contains(&fs::canonicalize(&app.workdir).unwrap()));
+    assert!(workdirs.contains(&fs::canonicalize(&other).unwrap()));
+}
+
+#[test]
+fn renaming_keeps_the_repos_and_skips_the_prompt() {
+    let (_dir, mut app, git) = test_app("work,jdoe,j@doe.dev,tok,\n");
+    registry::record(&mut app.repos, "work", InjectScope::Local, &app.workdir);
+    app.selected_index = Some(0);
+
+    press(&mut app, KeyCode::Char('e'));
+    press(&mut app, KeyCode::Char('2'));
+    press(&mut app, KeyCode::Enter);
+    assert!(app.current_screen == CurrentScreen::Main);
+    assert_eq!(app.entries[0].alias, "work2");
+    assert_eq!(app.repos_of(0).len(), 1);
+    // nothing is injected again, only the credential helpers are looked up
+    assert!(git
+        .calls()
+        .iter()
+        .all(|(_, args)| args[..3] == ["config", "--local", "--get-regexp"]));
+}
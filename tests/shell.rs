@@ -0,0 +1,182 @@
+mod common;
+
+use common::{git_init, local_config, set_local, test_app};
+use gat::cli::{self, status_command, Command};
+use gat::git::SystemGit;
+use gat::inject::InjectScope;
+use gat::registry;
+use gat::shell::{self, init_script, StatusJson};
+use std::process::ExitCode;
+use std::sync::Arc;
+
+#[test]
+fn hooks_exist_for_the_supported_shells() {
+    for shell in ["bash", "zsh", "fish"] {
+        assert!(init_script(shell)
+            .unwrap()
+            .contains("gat status --apply --quiet"));
+    }
+    assert!(init_script("tcsh").is_err());
+}
+
+#[test]
+fn apply_injects_the_profile_bound_by_the_remote_host() {
+    let (_dir, mut app, _) = test_app("work,jdoe,j@doe.dev,tok,,host=github.com\n");
+    app.git = Arc::new(SystemGit);
+    let workdir = app.workdir.clone();
+    git_init(&workdir);
+    set_local(
+        &workdir,
+        "remote.origin.url",
+        "https://github.com/doe/project.git",
+    );
+    set_local(&workdir, "user.name", "Someone Else");
+    set_local(&workdir, "user.email", "else@example.org");
+    app.set_workdir(&workdir).unwrap();
+
+    assert_eq!(shell::prompt(&app, shell::repo_status(&app).as_ref()), "!");
+    let mut out = Vec::new();
+    status_command(&mut app, false, true, &mut out).unwrap();
+    assert!(String::from_utf8(out)
+        .unwrap()
+        .contains("should use profile work"));
+    assert_eq!(
+        local_config(&workdir, "user.email").as_deref(),
+        Some("else@example.org")
+    );
+
+    let mut out = Vec::new();
+    status_command(&mut app, true, true, &mut out).unwrap();
+    assert!(String::from_utf8(out).unwrap().contains("switched"));
+    assert_eq!(
+        local_config(&workdir, "user.email").as_deref(),
+        Some("j@doe.dev")
+    );
+    assert_eq!(
+        shell::prompt(&app, shell::repo_status(&app).as_ref()),
+        "work"
+    );
+
+    // nothing to say once the identity is right
+    let mut out = Vec::new();
+    status_command(&mut app, true, true, &mut out).unwrap();
+    assert!(out.is_empty());
+
+    let status = shell::repo_status(&app);
+    let json = serde_json::to_value(StatusJson::new(&app, status.as_ref())).unwrap();
+    assert_eq!(json["active"], "work");
+    // applying recorded the repo in the registry
+    assert_eq!(json["bound_by"], "registry");
+    assert_eq!(json["mismatch"], false);
+}
+
+#[test]
+fn the_registry_wins_over_the_remote_host() {
+    let (_dir, mut app, _) =
+        test_app("work,jdoe,j@doe.dev,tok,,host=github.com\nhome,jd,jd@home.org,tok2,\n");
+    app.git = Arc::new(SystemGit);
+    let workdir = app.workdir.clone();
+    git_init(&workdir);
+    set_local(
+        &workdir,
+        "remote.origin.url",
+        "https://github.com/doe/project.git",
+    );
+    app.set_workdir(&workdir).unwrap();
+    registry::record(&mut app.repos, "home", InjectScope::Local, &workdir);
+
+    let status = shell::repo_status(&app).unwrap();
+    assert_eq!(status.bound.map(|(idx, _)| idx), Some(1));
+}
+
+#[test]
+fn the_hook_only_reads_the_store_and_registry() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = dir.path().join("gat/store");
+    let outside = dir.path().join("plain");
+    std::fs::create_dir(&outside).unwrap();
+    let prompt = || Command::Prompt;
+    let quiet_apply = || Command::Status {
+        json: false,
+        apply: true,
+        quiet: true,
+    };
+
+    // outside of repos nothing is printed and nothing is created
+    let mut out = Vec::new();
+    let code = cli::run_hook(
+        &quiet_apply(),
+        Some(store.clone()),
+        Vec::new(),
+        &outside,
+        &mut out,
+    );
+    assert!(code == Some(ExitCode::SUCCESS));
+    assert!(out.is_empty());
+    let mut out = Vec::new();
+    cli::run_hook(
+        &prompt(),
+        Some(store.clone()),
+        Vec::new(),
+        &outside,
+        &mut out,
+    )
+    .unwrap();
+    assert_eq!(out, b"\n");
+    assert!(!store.parent().unwrap().exists());
+
+    let repo = dir.path().join("repo");
+    std::fs::create_dir(&repo).unwrap();
+    git_init(&repo);
+    set_local(
+        &repo,
+        "remote.origin.url",
+        "https://github.com/doe/project.git",
+    );
+    set_local(&repo, "user.name", "jdoe");
+    set_local(&repo, "user.email", "j@doe.dev");
+    std::fs::create_dir(store.parent().unwrap()).unwrap();
+    std::fs::write(&store, "work,jdoe,j@doe.dev,tok,,host=github.com\n").unwrap();
+    let mut out = Vec::new();
+    cli::run_hook(&prompt(), Some(store.clone()), Vec::new(), &repo, &mut out).unwrap();
+    assert_eq!(out, b"work\n");
+    let mut out = Vec::new();
+    cli::run_hook(
+        &quiet_apply(),
+        Some(store.clone()),
+        Vec::new(),
+        &repo,
+        &mut out,
+    )
+    .unwrap();
+    assert!(out.is_empty());
+    let mut files: Vec<_> = std::fs::read_dir(store.parent().unwrap())
+        .unwrap()
+        .map(|file| file.unwrap().file_name())
+        .collect();
+    files.sort();
+    assert_eq!(files, ["store"]);
+
+    // switching needs the full app
+    set_local(&repo, "user.email", "else@example.org");
+    let mut out = Vec::new();
+    assert!(cli::run_hook(
+        &quiet_apply(),
+        Some(store.clone()),
+        Vec::new(),
+        &repo,
+        &mut out
+    )
+    .is_none());
+    assert!(cli::run_hook(
+        &Command::List {
+            json: false,
+            show_secrets: false
+        },
+        Some(store),
+        Vec::new(),
+        &repo,
+        &mut out
+    )
+    .is_none());
+}
@@ -0,0 +1,188 @@
+mod common;
+
+use common::{entry, test_app};
+use gat::app::{CurrentScreen, Entry};
+use gat::handler::handle_key_press;
+use gat::store;
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::fs;
+use std::path::PathBuf;
+
+#[test]
+fn git_config_overrides_round_trip_through_the_store_format() {
+    let mut work = entry("work", "jdoe", "j@doe.dev", "tok", "");
+    work.git_config = vec![
+        ("pull.rebase".to_string(), "true".to_string()),
+        (
+            "url.https://a,b=c%/.insteadOf".to_string(),
+            "git@x:".to_string(),
+        ),
+    ];
+    let line = work.to_string();
+    assert_eq!(line.lines().count(), 1);
+
+    let parsed = Entry::from_line(&line).unwrap();
+    assert_eq!(parsed.git_config, work.git_config);
+    assert_eq!(parsed.to_string(), line);
+}
+
+#[test]
+fn lines_without_overrides_keep_the_old_format() {
+    let line = "work,jdoe,j@doe.dev,tok,John";
+    let parsed = Entry::from_line(line).unwrap();
+    assert!(parsed.git_config.is_empty());
+    assert_eq!(parsed.to_string(), line);
+}
+
+#[test]
+fn merge_keeps_our_edits_and_their_additions() {
+    let base = store::parse_entries("a,1,a@x,t,\nb,2,b@x,t,\n");
+    let disk = store::parse_entries("a,1,a@x,t,\nb,2,b@x,t,\nc,3,c@x,t,\n");
+    let ours = store::parse_entries("a,9,a@x,t,\n");
+    let merged = store::serialize_entries(&store::merge_entries(&base, &disk, &ours));
+    assert_eq!(merged, "a,9,a@x,t,\nc,3,c@x,t,\n");
+}
+
+#[test]
+fn store_resolution_prefers_flag_then_env_then_xdg_then_home() {
+    let dir = tempfile::tempdir().unwrap();
+    let home = dir.path();
+    let flag = Some(PathBuf::from("/flag"));
+    let var = Some(PathBuf::from("/var"));
+    let xdg = Some(home.join("xdg"));
+    let resolve = |flag: &Option<PathBuf>, var: &Option<PathBuf>| {
+        store::resolve_store(flag.clone(), var.clone(), xdg.clone(), Some(home)).unwrap()
+    };
+    assert_eq!(resolve(&flag, &var), PathBuf::from("/flag"));
+    assert_eq!(resolve(&None, &var), PathBuf::from("/var"));
+    // the xdg store is only used once it exists
+    assert_eq!(resolve(&None, &None), home.join(".gat"));
+    fs::create_dir_all(home.join("xdg").join("gat")).unwrap();
+    fs::write(home.join("xdg").join("gat").join("store"), "").unwrap();
+    assert_eq!(
+        resolve(&None, &None),
+        home.join("xdg").join("gat").join("store")
+    );
+
+    assert!(store::resolve_store(None, None, None, None).is_err());
+}
+
+#[test]
+fn shared_profiles_are_listed_but_never_saved_or_changed() {
+    let (dir, mut app, _) = test_app("work,jdoe,j@doe.dev,tok,\n");
+    let team = dir.path().join("team");
+    fs::write(&team, "work,other,o@x,t2,\nci,bot,ci@x,t3,\n").unwrap();
+    app.load_shared_stores(std::slice::from_ref(&team));
+    // the own profile shadows the shared one with the same alias
+    let aliases: Vec<&str> = app.entries.iter().map(|e| e.alias.as_str()).collect();
+    assert_eq!(aliases, ["work", "ci"]);
+    assert_eq!(app.store_of("ci"), team.as_path());
+
+    app.selected_index = Some(1);
+    handle_key_press(
+        KeyEvent::new(KeyCode::Char('d'), KeyModifiers::NONE),
+        &mut app,
+    );
+    assert_eq!(app.entries.len(), 2);
+    assert!(app.status.as_deref().unwrap().contains("read-only"));
+
+    app.alias_input.set("home");
+    app.store_entries();
+    let aliases: Vec<&str> = app.entries.iter().map(|e| e.alias.as_str()).collect();
+    assert_eq!(aliases, ["work", "home", "ci"]);
+    let saved = fs::read_to_string(&app.save_file).unwrap();
+    assert_eq!(saved, "work,jdoe,j@doe.dev,tok,\nhome,,,,\n");
+    assert_eq!(
+        fs::read_to_string(&team).unwrap(),
+        "work,other,o@x,t2,\nci,bot,ci@x,t3,\n"
+    );
+}
+
+#[test]
+fn unreadable_stores_do_not_panic() {
+    let dir = tempfile::tempdir().unwrap();
+    let save_file = dir.path().join("missing").join("store");
+    let app = gat::app::App::with_paths(dir.path().to_path_buf(), save_file, PathBuf::from("."));
+    assert!(app.entries.is_empty());
+}
+
+#[cfg(unix)]
+#[test]
+fn a_new_store_and_its_trash_are_readable_by_the_owner_only() {
+    use std::os::unix::fs::PermissionsExt;
+    let dir = tempfile::tempdir().unwrap();
+    let save_file = dir.path().join("store");
+    let mut app = gat::app::App::with_paths(
+        dir.path().to_path_buf(),
+        save_file.clone(),
+        PathBuf::from("."),
+    );
+    app.alias_input.insert_str("work");
+    app.token_input.insert_str("tok");
+    app.store_entries();
+    app.selected_index = Some(0);
+    app.delete_current_entry();
+    assert!(!app.dirty);
+
+    for file in [save_file, app.trash_file.clone()] {
+        let mode = fs::metadata(&file).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600, "{}", file.display());
+    }
+}
+
+#[test]
+fn failed_writes_leave_the_old_store_intact() {
+    let (_dir, mut app, _) = test_app("work,jdoe,j@doe.dev,tok,\n");
+    // the temporary file cannot be created where a directory is in the way
+    let mut tmp = app.save_file.as_os_str().to_owned();
+    tmp.push(".tmp");
+    fs::create_dir(&tmp).unwrap();
+
+    app.alias_input.insert_str("home");
+    app.store_entries();
+    assert!(app.status.as_deref().unwrap().starts_with("Saving failed"));
+    assert_eq!(
+        fs::read_to_string(&app.save_file).unwrap(),
+        "work,jdoe,j@doe.dev,tok,\n"
+    );
+}
+
+#[test]
+fn a_second_writer_waits_for_the_lock_and_merges() {
+    let (_dir, mut first, _) = test_app("work,jdoe,j@doe.dev,tok,\n");
+    // another instance working on the same store
+    let (_other, mut second, _) = test_app("");
+    second.save_file = first.save_file.clone();
+    second.reload_store();
+
+    let lock = store::StoreLock::acquire(&first.save_file).unwrap();
+    let writer = std::thread::spawn(move || {
+        second.alias_input.insert_str("home");
+        second.store_entries();
+        second
+    });
+    // the first instance saves while the second one waits for the lock
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    fs::write(
+        &first.save_file,
+        "work,jdoe,j@doe.dev,tok,\nci,bot,ci@x,t,\n",
+    )
+    .unwrap();
+    drop(lock);
+
+    let mut second = writer.join().unwrap();
+    assert!(second.current_screen == CurrentScreen::Conflict);
+    assert_eq!(
+        fs::read_to_string(&second.save_file).unwrap(),
+        "work,jdoe,j@doe.dev,tok,\nci,bot,ci@x,t,\n"
+    );
+    second.merge_external_changes();
+    assert_eq!(
+        fs::read_to_string(&second.save_file).unwrap(),
+        "work,jdoe,j@doe.dev,tok,\nhome,,,,\nci,bot,ci@x,t,\n"
+    );
+
+    first.reload_store();
+    let aliases: Vec<&str> = first.entries.iter().map(|e| e.alias.as_str()).collect();
+    assert_eq!(aliases, ["work", "home", "ci"]);
+}
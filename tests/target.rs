@@ -0,0 +1,56 @@
+mod common;
+
+use common::{git_init, test_app};
+use gat::app::CurrentScreen;
+use gat::input::complete_path;
+use std::fs;
+
+#[test]
+fn paths_complete_as_far_as_unambiguous() {
+    let dir = tempfile::tempdir().unwrap();
+    for name in ["project-a", "project-b", "other", ".hidden"] {
+        fs::create_dir(dir.path().join(name)).unwrap();
+    }
+    fs::write(dir.path().join("project-file"), "").unwrap();
+    let base = format!("{}/", dir.path().display());
+
+    let (completed, matches) = complete_path(&format!("{}pro", base), dir.path());
+    assert_eq!(completed, format!("{}project-", base));
+    assert_eq!(matches, ["project-a", "project-b"]);
+
+    let (completed, _) = complete_path(&format!("{}ot", base), dir.path());
+    assert_eq!(completed, format!("{}other/", base));
+
+    let (_, matches) = complete_path(&base, dir.path());
+    assert_eq!(matches, ["other", "project-a", "project-b"]);
+
+    // ~ is the home directory, the typed form is kept
+    let (completed, _) = complete_path("~/.hid", dir.path());
+    assert_eq!(completed, "~/.hidden/");
+}
+
+#[test]
+fn choosing_a_repo_retargets_the_app() {
+    let (dir, mut app, _) = test_app("");
+    app.git = std::sync::Arc::new(gat::git::SystemGit);
+    let repo = dir.path().join("elsewhere");
+    fs::create_dir(&repo).unwrap();
+    git_init(&repo);
+
+    app.open_repo_picker();
+    app.repo_path_input
+        .set(&format!("{}/nowhere", dir.path().display()));
+    app.choose_repo();
+    assert!(app.current_screen == CurrentScreen::ChoosingRepo);
+    assert!(app.status.as_deref().unwrap().contains("not a directory"));
+
+    app.repo_path_input
+        .set(&format!("{}/elsewh", dir.path().display()));
+    app.complete_repo_path();
+    app.choose_repo();
+    assert!(app.current_screen == CurrentScreen::Main);
+    let target = app.target.clone().unwrap();
+    assert_eq!(target.root, fs::canonicalize(&repo).unwrap());
+    // a fresh repo is on its unborn default branch
+    assert!(target.branch.is_some());
+}
@@ -0,0 +1,110 @@
+mod common;
+
+use common::test_app;
+use gat::app::{App, CurrentScreen};
+use gat::handler::handle_key_press;
+use gat::template;
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+fn press(app: &mut App, code: KeyCode) {
+    handle_key_press(KeyEvent::new(code, KeyModifiers::NONE), app);
+}
+
+fn type_text(app: &mut App, text: &str) {
+    for c in text.chars() {
+        press(app, KeyCode::Char(c));
+    }
+}
+
+#[test]
+fn duplicates_keep_everything_but_the_alias() {
+    let (_dir, mut app, _git) =
+        test_app("work,jdoe,j@doe.dev,tok,John,host=github.com,cfg.commit.gpgsign=true\n");
+    app.selected_index = Some(0);
+    press(&mut app, KeyCode::Char('y'));
+    assert!(app.current_screen == CurrentScreen::Editing);
+    assert_eq!(app.alias_input.value(), "work-copy");
+    assert_eq!(app.email_input.value(), "j@doe.dev");
+
+    press(&mut app, KeyCode::Enter);
+    assert_eq!(app.entries.len(), 2);
+    assert_eq!(app.entries[0].alias, "work");
+    assert_eq!(app.entries[1].alias, "work-copy");
+    assert_eq!(app.entries[1].host, "github.com");
+    assert_eq!(app.entries[1].git_config, app.entries[0].git_config);
+}
+
+#[test]
+fn aliases_stay_unique_and_non_empty() {
+    let (_dir, mut app, _git) = test_app("work,jdoe,j@doe.dev,tok,\nwork-copy,jd,jd@doe.dev,,\n");
+    app.selected_index = Some(0);
+    press(&mut app, KeyCode::Char('y'));
+    assert_eq!(app.alias_input.value(), "work-copy-2");
+
+    // renaming the copy after an existing profile keeps the editor open
+    app.alias_input.set("work");
+    press(&mut app, KeyCode::Enter);
+    assert!(app.current_screen == CurrentScreen::Editing);
+    assert!(app
+        .status
+        .as_deref()
+        .unwrap()
+        .contains("work already exists"));
+    app.alias_input.set(" ");
+    press(&mut app, KeyCode::Enter);
+    assert!(app.current_screen == CurrentScreen::Editing);
+    assert_eq!(app.entries.len(), 2);
+
+    // an edit may keep its own alias
+    press(&mut app, KeyCode::Esc);
+    app.selected_index = Some(0);
+    press(&mut app, KeyCode::Char('e'));
+    press(&mut app, KeyCode::Enter);
+    assert!(app.current_screen == CurrentScreen::Main);
+    assert_eq!(app.entries[0].alias, "work");
+}
+
+#[test]
+fn templates_seed_new_profiles_and_persist() {
+    let (_dir, mut app, _git) = test_app(
+        "corp,jdoe,jdoe@corp.example,tok,,host=git.corp.example,provider=gitlab,cfg.commit.gpgsign=true\n",
+    );
+    // without templates % opens the editor right away
+    press(&mut app, KeyCode::Char('%'));
+    assert!(app.current_screen == CurrentScreen::Editing);
+    press(&mut app, KeyCode::Esc);
+
+    app.selected_index = Some(0);
+    app.save_template_from_selected();
+    let saved = template::load_templates(&app.templates_file);
+    assert_eq!(saved, app.templates);
+    assert_eq!(saved[0].email_domain, "corp.example");
+
+    press(&mut app, KeyCode::Char('%'));
+    assert!(app.current_screen == CurrentScreen::ChoosingTemplate);
+    press(&mut app, KeyCode::Char('j'));
+    press(&mut app, KeyCode::Enter);
+    assert!(app.current_screen == CurrentScreen::Editing);
+    assert_eq!(app.token_input.value(), "");
+    type_text(&mut app, "ci");
+    press(&mut app, KeyCode::Tab);
+    type_text(&mut app, "ci-bot");
+    press(&mut app, KeyCode::Tab);
+    // the cursor waits in front of the seeded domain
+    type_text(&mut app, "ci");
+    press(&mut app, KeyCode::Enter);
+
+    let created = &app.entries[1];
+    assert_eq!(created.alias, "ci");
+    assert_eq!(created.email, "ci@corp.example");
+    assert_eq!(created.host, "git.corp.example");
+    assert_eq!(created.provider, app.entries[0].provider);
+    assert_eq!(created.git_config, app.entries[0].git_config);
+    assert_eq!(created.pa_token, "");
+
+    press(&mut app, KeyCode::Char('%'));
+    press(&mut app, KeyCode::Char('j'));
+    press(&mut app, KeyCode::Char('d'));
+    assert!(app.templates.is_empty());
+    assert!(template::load_templates(&app.templates_file).is_empty());
+}